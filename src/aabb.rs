@@ -0,0 +1,101 @@
+use crate::ray::Ray;
+use crate::vec3::Point3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub minimum: Point3,
+    pub maximum: Point3,
+}
+
+impl Aabb {
+    pub fn new(minimum: Point3, maximum: Point3) -> Self {
+        Aabb { minimum, maximum }
+    }
+
+    /// Smallest box containing both `box0` and `box1`.
+    pub fn surrounding_box(box0: Aabb, box1: Aabb) -> Self {
+        let minimum = Point3::new(
+            box0.minimum.x.min(box1.minimum.x),
+            box0.minimum.y.min(box1.minimum.y),
+            box0.minimum.z.min(box1.minimum.z),
+        );
+        let maximum = Point3::new(
+            box0.maximum.x.max(box1.maximum.x),
+            box0.maximum.y.max(box1.maximum.y),
+            box0.maximum.z.max(box1.maximum.z),
+        );
+        Aabb { minimum, maximum }
+    }
+
+    /// Grows any axis thinner than `delta` so flat objects (e.g. axis-aligned
+    /// triangles) still produce a box the slab test can hit.
+    pub fn pad(self, delta: f64) -> Self {
+        let mut padded = self;
+        for axis in 0..3 {
+            if padded.maximum[axis] - padded.minimum[axis] < delta {
+                padded.minimum[axis] -= delta / 2.0;
+                padded.maximum[axis] += delta / 2.0;
+            }
+        }
+        padded
+    }
+
+    pub fn centroid(&self) -> Point3 {
+        0.5 * (self.minimum + self.maximum)
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        let d = self.maximum - self.minimum;
+        2.0 * (d.x * d.y + d.y * d.z + d.z * d.x)
+    }
+
+    /// Slab test: does the ray pass through the box anywhere in `(t_min, t_max)`?
+    pub fn hit(&self, ray: &Ray, mut t_min: f64, mut t_max: f64) -> bool {
+        for axis in 0..3 {
+            let inv_d = 1.0 / ray.direction[axis];
+            let mut t0 = (self.minimum[axis] - ray.origin[axis]) * inv_d;
+            let mut t1 = (self.maximum[axis] - ray.origin[axis]) * inv_d;
+            if inv_d < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            t_min = if t0 > t_min { t0 } else { t_min };
+            t_max = if t1 < t_max { t1 } else { t_max };
+            if t_max <= t_min {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec3::Vec3;
+
+    #[test]
+    fn test_surrounding_box() {
+        let a = Aabb::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0));
+        let b = Aabb::new(Point3::new(-1.0, 0.5, 2.0), Point3::new(0.5, 3.0, 4.0));
+        let exp = Aabb::new(Point3::new(-1.0, 0.0, 0.0), Point3::new(1.0, 3.0, 4.0));
+        assert_eq!(exp, Aabb::surrounding_box(a, b));
+    }
+
+    #[test]
+    fn test_surface_area() {
+        let a = Aabb::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 2.0, 3.0));
+        assert!(approx_eq!(f64, a.surface_area(), 22.0, ulps = 2));
+    }
+
+    #[test]
+    fn test_hit() {
+        let a = Aabb::new(Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0));
+        let towards = Ray::new(Point3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        let away = Ray::new(Point3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, -1.0));
+        let beside = Ray::new(Point3::new(2.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(a.hit(&towards, 0.001, f64::INFINITY));
+        assert!(!a.hit(&towards, 0.001, 3.0));
+        assert!(!a.hit(&away, 0.001, f64::INFINITY));
+        assert!(!a.hit(&beside, 0.001, f64::INFINITY));
+    }
+}
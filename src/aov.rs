@@ -0,0 +1,207 @@
+//! Arbitrary output variables (AOVs): per-pixel buffers describing the first
+//! surface each camera ray hits, written alongside the image for compositing
+//! and denoising.
+
+use crate::hittable::HitRecord;
+use crate::ray::Ray;
+use crate::render::mix;
+use crate::vec3::Color;
+use serde::Deserialize;
+
+/// One kind of AOV. Every kind but the sample count is zero where the camera
+/// ray escapes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Aov {
+    /// Base color of the material.
+    Albedo,
+    /// Shading normal, facing the camera.
+    Normal,
+    /// Distance from the camera.
+    Depth,
+    /// Hit point in world space.
+    Position,
+    /// Stable ID of the object, from `hittable::tagged::TaggedHittable`.
+    ObjectId,
+    /// Stable ID of the material, from `material::tagged::TaggedMaterial`.
+    MaterialId,
+    /// Number of samples the pixel took, which varies with adaptive sampling.
+    /// Filled in by `render_image` rather than from a hit.
+    SampleCount,
+}
+
+impl Aov {
+    pub const ALL: [Aov; 7] = [
+        Aov::Albedo,
+        Aov::Normal,
+        Aov::Depth,
+        Aov::Position,
+        Aov::ObjectId,
+        Aov::MaterialId,
+        Aov::SampleCount,
+    ];
+
+    /// Name of the EXR layer, or suffix of the separate image, holding the
+    /// AOV.
+    pub fn name(self) -> &'static str {
+        match self {
+            Aov::Albedo => "albedo",
+            Aov::Normal => "normal",
+            Aov::Depth => "depth",
+            Aov::Position => "position",
+            Aov::ObjectId => "object_id",
+            Aov::MaterialId => "material_id",
+            Aov::SampleCount => "sample_count",
+        }
+    }
+
+    /// The AOV for a camera `ray` that hit `record`. Scalars fill all three
+    /// channels.
+    pub fn value(self, ray: &Ray, record: Option<&HitRecord>) -> Color {
+        let record = match record {
+            Some(record) => record,
+            None => return Color::default(),
+        };
+        let scalar = |value: f64| Color::new(value, value, value);
+        match self {
+            Aov::Albedo => record.material.albedo(record),
+            Aov::Normal => record.normal,
+            Aov::Depth => scalar(record.t * ray.direction.length()),
+            Aov::Position => record.p,
+            Aov::ObjectId => scalar(record.object_id as f64),
+            Aov::MaterialId => scalar(record.material.id() as f64),
+            Aov::SampleCount => Color::default(),
+        }
+    }
+
+    /// Whether a pixel averages its samples' values. IDs are not averaged,
+    /// since a blend of two IDs means nothing; the pixel keeps its first
+    /// sample's.
+    pub fn is_filtered(self) -> bool {
+        !matches!(self, Aov::ObjectId | Aov::MaterialId | Aov::SampleCount)
+    }
+
+    /// Maps a whole buffer into `[0, 1]` for 8-bit images. Sample counts are
+    /// divided by the largest first, so the heat map spans the buffer's range;
+    /// everything else goes through `display` as is.
+    pub fn display_buffer(self, buffer: &[Color]) -> Vec<Color> {
+        let scale = if self == Aov::SampleCount {
+            1.0 / buffer.iter().fold(1.0, |max: f64, value| max.max(value.x))
+        } else {
+            1.0
+        };
+        buffer
+            .iter()
+            .map(|&value| self.display(scale * value))
+            .collect()
+    }
+
+    /// Maps a value into `[0, 1]` for 8-bit images: normals per axis, depth
+    /// as `1 / (1 + d)`, positions as their fractional part, IDs as a
+    /// distinct color each, and sample counts, as a fraction of the most
+    /// taken, on a black-red-yellow-white heat map.
+    pub fn display(self, value: Color) -> Color {
+        if value == Color::default() {
+            return value;
+        }
+        match self {
+            Aov::Albedo => value,
+            Aov::Normal => 0.5 * (value + Color::new(1.0, 1.0, 1.0)),
+            Aov::Depth => {
+                let shade = 1.0 / (1.0 + value.x);
+                Color::new(shade, shade, shade)
+            }
+            Aov::Position => Color::new(
+                value.x - value.x.floor(),
+                value.y - value.y.floor(),
+                value.z - value.z.floor(),
+            ),
+            Aov::ObjectId | Aov::MaterialId => id_color(value.x as u32),
+            Aov::SampleCount => {
+                let heat = |offset: f64| (3.0 * value.x - offset).clamp(0.0, 1.0);
+                Color::new(heat(0.0), heat(1.0), heat(2.0))
+            }
+        }
+    }
+}
+
+/// A distinct color for an object or material ID, the same on every run and
+/// toolchain.
+pub fn id_color(id: u32) -> Color {
+    let bits = mix(0, id as u64);
+    let channel = |shift: u32| ((bits >> shift) & 0xff) as f64 / 255.0;
+    Color::new(channel(0), channel(8), channel(16))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable::sphere::Sphere;
+    use crate::hittable::tagged::TaggedHittable;
+    use crate::hittable::Hittable;
+    use crate::material::lambertian::Lambertian;
+    use crate::material::tagged::TaggedMaterial;
+    use crate::vec3::{Point3, Vec3};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_values() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let albedo = Color::new(0.2, 0.4, 0.6);
+        let sphere = TaggedHittable::new(
+            Sphere::new(
+                Point3::default(),
+                1.0,
+                TaggedMaterial::new(Lambertian::new(albedo), 7),
+            ),
+            3,
+        );
+        let ray = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -2.0));
+        let record = sphere.hit(&ray, 0.001, f64::INFINITY, &mut rng);
+        let value = |aov: Aov| aov.value(&ray, record.as_ref());
+
+        assert_eq!(albedo, value(Aov::Albedo));
+        assert_eq!(Vec3::new(0.0, 0.0, 1.0), value(Aov::Normal));
+        assert!(approx_eq!(f64, 4.0, value(Aov::Depth).x, epsilon = 1e-12));
+        assert!(approx_eq!(
+            f64,
+            1.0,
+            value(Aov::Position).z,
+            epsilon = 1e-12
+        ));
+        assert_eq!(Color::new(3.0, 3.0, 3.0), value(Aov::ObjectId));
+        assert_eq!(Color::new(7.0, 7.0, 7.0), value(Aov::MaterialId));
+
+        for &aov in Aov::ALL.iter() {
+            assert_eq!(Color::default(), aov.value(&ray, None));
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            Color::new(0.5, 0.5, 1.0),
+            Aov::Normal.display(Vec3::new(0.0, 0.0, 1.0))
+        );
+        assert_eq!(
+            Color::new(0.25, 0.25, 0.25),
+            Aov::Depth.display(Color::new(3.0, 3.0, 3.0))
+        );
+        let id = |n: f64| Aov::ObjectId.display(Color::new(n, n, n));
+        assert_eq!(
+            Color::new(193.0 / 255.0, 92.0 / 255.0, 2.0 / 255.0),
+            id_color(1)
+        );
+        assert_ne!(id(4.0), id(5.0));
+        assert_eq!(Color::default(), id(0.0));
+        // Misses are black above, but untagged hits in the debug view are not.
+        assert_ne!(Color::default(), id_color(0));
+
+        let counts = [4.0, 8.0, 16.0].map(|n| Color::new(n, n, n));
+        let heat = Aov::SampleCount.display_buffer(&counts);
+        assert_eq!(Color::new(0.75, 0.0, 0.0), heat[0]);
+        assert_eq!(Color::new(1.0, 0.5, 0.0), heat[1]);
+        assert_eq!(Color::new(1.0, 1.0, 1.0), heat[2]);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::ray::Ray;
 use crate::vec3::{Point3, Vec3};
+use rand::{Rng, RngCore};
 
 #[derive(Clone, Copy)]
 pub struct Camera {
@@ -10,6 +11,10 @@ pub struct Camera {
     u: Vec3,
     v: Vec3,
     lens_radius: f64,
+    /// Shutter open and close times. Rays are cast at uniformly random times in
+    /// between.
+    time0: f64,
+    time1: f64,
 }
 
 impl Default for Camera {
@@ -63,16 +68,33 @@ impl Camera {
             u,
             v,
             lens_radius,
+            time0: 0.0,
+            time1: 0.0,
         }
     }
 
-    pub fn get_ray(&self, s: f64, t: f64) -> Ray {
-        let rd = self.lens_radius * Vec3::random_in_unit_disk();
+    /// Keeps the shutter open from `time0` to `time1`, so objects that move in
+    /// that interval are motion blurred.
+    pub fn with_shutter(self, time0: f64, time1: f64) -> Self {
+        Camera {
+            time0,
+            time1,
+            ..self
+        }
+    }
+
+    pub fn get_ray(&self, s: f64, t: f64, rng: &mut dyn RngCore) -> Ray {
+        let rd = self.lens_radius * Vec3::random_in_unit_disk(rng);
         let offset = self.u * rd.x + self.v * rd.y;
 
         let origin = self.origin + offset;
         let direction =
             self.lower_left_corner + s * self.horizontal + t * self.vertical - self.origin - offset;
-        Ray::new(origin, direction)
+        let time = if self.time0 < self.time1 {
+            rng.gen_range(self.time0, self.time1)
+        } else {
+            self.time0
+        };
+        Ray::with_time(origin, direction, time)
     }
 }
@@ -0,0 +1,633 @@
+use clap::builder::{RangedI64ValueParser, RangedU64ValueParser};
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, ValueEnum};
+use rand::Rng;
+use ray_tracing_in_one_weekend::aov::Aov;
+use ray_tracing_in_one_weekend::camera::Camera;
+use ray_tracing_in_one_weekend::hittable::tagged::tag_objects;
+use ray_tracing_in_one_weekend::hittable::Hittable;
+use ray_tracing_in_one_weekend::integrator::IntegratorKind;
+use ray_tracing_in_one_weekend::output::Format;
+use ray_tracing_in_one_weekend::render::{AdaptiveSampling, Background, Bounds};
+use ray_tracing_in_one_weekend::tone_map::{DisplayTransform, ToneMap};
+use ray_tracing_in_one_weekend::vec3::Color;
+use ray_tracing_in_one_weekend::world;
+use std::path::PathBuf;
+
+pub const DEFAULT_WIDTH: u32 = 3840;
+pub const DEFAULT_ASPECT_RATIO: f64 = 16.0 / 9.0;
+pub const DEFAULT_SAMPLES_PER_PIXEL: usize = 500;
+pub const DEFAULT_DEPTH: i32 = 50;
+pub const DEFAULT_OCCLUSION_DISTANCE: f64 = 1.0;
+
+/// Render a scene with the ray tracer from Ray Tracing in One Weekend.
+///
+/// Renders either a built-in scene or a TOML scene file. Settings given on the
+/// command line override those in the scene file.
+#[derive(Parser, Debug)]
+#[command(
+    version,
+    about,
+    after_help = "Exit status is 0 on success, 1 if the render fails (e.g. an invalid scene \
+                  file) and 2 for invalid arguments."
+)]
+pub struct Cli {
+    /// TOML scene file to render instead of a built-in scene
+    #[arg(value_name = "SCENE_FILE", conflicts_with = "scene")]
+    pub scene_file: Option<PathBuf>,
+
+    /// Built-in scene to render [default: book-cover]
+    #[arg(short, long, value_enum)]
+    pub scene: Option<BuiltInScene>,
+
+    /// Image width in pixels, at least 2 [default: 3840, or the scene file's
+    /// width]
+    #[arg(short, long, value_name = "PIXELS", value_parser = RangedU64ValueParser::<u32>::new().range(2..=u32::MAX as u64))]
+    pub width: Option<u32>,
+
+    /// Image width over height, as a number or `W:H` [default: 16:9]
+    #[arg(
+        short,
+        long,
+        value_name = "RATIO",
+        value_parser = parse_aspect_ratio,
+        conflicts_with = "scene_file"
+    )]
+    pub aspect_ratio: Option<f64>,
+
+    /// Samples per pixel [default: 500, or the scene file's]
+    #[arg(short = 'n', long, value_name = "N", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    pub samples_per_pixel: Option<usize>,
+
+    /// Sample adaptively: stop sampling each pixel once the estimated error of
+    /// its luminance falls below this, e.g. 0.005, with the samples per pixel
+    /// as the most any pixel takes [default: off, or the scene file's]
+    #[arg(long, value_name = "THRESHOLD", value_parser = parse_threshold)]
+    pub adaptive: Option<f64>,
+
+    /// Samples every pixel takes before adaptive sampling may stop it
+    /// [default: 16, or the scene file's]
+    #[arg(long, value_name = "N", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    pub min_samples: Option<usize>,
+
+    /// Maximum number of bounces per ray [default: 50, or the scene file's]
+    #[arg(short, long, value_name = "N", value_parser = RangedI64ValueParser::<i32>::new().range(1..))]
+    pub depth: Option<i32>,
+
+    /// Where to write the rendered image
+    #[arg(short, long, value_name = "PATH", default_value = "image.png")]
+    pub output: PathBuf,
+
+    /// Image file format [default: from the output's extension: exr, hdr or
+    /// pfm for floating point, anything else 8-bit]
+    #[arg(short, long, value_enum)]
+    pub format: Option<FormatName>,
+
+    /// Number of render threads [default: one per logical CPU]
+    #[arg(short = 'j', long, value_name = "N", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    pub threads: Option<usize>,
+
+    /// Seed for randomly generated scenes and for sampling [default: 0, or the
+    /// scene file's]
+    #[arg(long, value_name = "N")]
+    pub seed: Option<u64>,
+
+    /// How to turn rays into colors [default: path, or the scene file's]
+    #[arg(short, long, value_enum)]
+    pub integrator: Option<IntegratorName>,
+
+    /// How far away geometry occludes with `--integrator ambient-occlusion`
+    /// [default: 1, or the scene file's]
+    #[arg(long, value_name = "DISTANCE", value_parser = parse_distance)]
+    pub occlusion_distance: Option<f64>,
+
+    /// Brighten 8-bit output by this many stops, or darken it if negative
+    /// [default: 0, or the scene file's]
+    #[arg(short, long, value_name = "STOPS", allow_negative_numbers = true, value_parser = parse_exposure)]
+    pub exposure: Option<f64>,
+
+    /// How 8-bit output maps bright values into range [default: clamp, or the
+    /// scene file's]
+    #[arg(short, long, value_enum)]
+    pub tone_map: Option<ToneMapName>,
+
+    /// Extra per-pixel buffers to write, as EXR layers or separate images
+    /// [default: none, or the scene file's]
+    #[arg(long = "aov", value_name = "AOV", value_enum, value_delimiter = ',')]
+    pub aovs: Vec<AovName>,
+
+    /// Denoise the image, guided by the albedo, normal and depth AOVs, for
+    /// quick previews at low sample counts
+    #[arg(long)]
+    pub denoise: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum IntegratorName {
+    /// Path tracing with light sampling
+    Path,
+    /// Ambient occlusion
+    AmbientOcclusion,
+    /// Mirror and glass reflections with direct lighting only
+    Whitted,
+    /// Surface normals as colors
+    Normals,
+    /// Distance to the nearest surface
+    Depth,
+    /// A flat color per material
+    MaterialIds,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum FormatName {
+    /// 8-bit PNG, gamma corrected
+    Png,
+    /// OpenEXR with linear 32-bit float channels
+    Exr,
+    /// Radiance RGBE
+    Hdr,
+    /// Portable float map
+    Pfm,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum ToneMapName {
+    /// Clip values above one
+    Clamp,
+    /// x / (1 + x)
+    Reinhard,
+    /// ACES filmic curve
+    Aces,
+    /// Hable's filmic curve
+    Hable,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum AovName {
+    /// Base color of the first surface hit
+    Albedo,
+    /// Shading normal
+    Normal,
+    /// Distance from the camera
+    Depth,
+    /// World-space hit point
+    Position,
+    /// Object ID
+    ObjectId,
+    /// Material ID
+    MaterialId,
+    /// Number of samples taken, as a heat map in 8-bit images
+    SampleCount,
+}
+
+impl Cli {
+    /// The image size, given the scene file's, if any. `--width` keeps the
+    /// scene file's aspect ratio. Fails if the image would be less than 2
+    /// pixels high, since rendering spreads pixels from one edge of the view to
+    /// the other.
+    pub fn bounds(&self, scene: Option<Bounds>) -> Result<Bounds, clap::Error> {
+        let (width, height) = match (scene, self.width) {
+            (Some(bounds), None) => return Ok(bounds),
+            (Some(bounds), Some(width)) => (
+                width,
+                width as f64 * bounds.height as f64 / bounds.width as f64,
+            ),
+            (None, width) => {
+                let width = width.unwrap_or(DEFAULT_WIDTH);
+                let aspect_ratio = self.aspect_ratio.unwrap_or(DEFAULT_ASPECT_RATIO);
+                (width, width as f64 / aspect_ratio)
+            }
+        };
+        let height = height as u32;
+        if height < 2 {
+            return Err(Cli::command().error(
+                ErrorKind::ValueValidation,
+                format!(
+                    "an image {} pixels wide would be less than 2 pixels high",
+                    width
+                ),
+            ));
+        }
+        Ok(Bounds { width, height })
+    }
+
+    /// The AOVs to write, given the ones the scene asked for.
+    pub fn aovs(&self, scene: &[Aov]) -> Vec<Aov> {
+        if self.aovs.is_empty() {
+            return scene.to_vec();
+        }
+        let mut aovs = Vec::new();
+        for name in self.aovs.iter() {
+            let aov = match name {
+                AovName::Albedo => Aov::Albedo,
+                AovName::Normal => Aov::Normal,
+                AovName::Depth => Aov::Depth,
+                AovName::Position => Aov::Position,
+                AovName::ObjectId => Aov::ObjectId,
+                AovName::MaterialId => Aov::MaterialId,
+                AovName::SampleCount => Aov::SampleCount,
+            };
+            if !aovs.contains(&aov) {
+                aovs.push(aov);
+            }
+        }
+        aovs
+    }
+
+    /// The display transform for 8-bit output, given the scene's.
+    pub fn display(&self, scene: DisplayTransform) -> DisplayTransform {
+        let tone_map = match self.tone_map {
+            None => scene.tone_map,
+            Some(ToneMapName::Clamp) => ToneMap::Clamp,
+            Some(ToneMapName::Reinhard) => ToneMap::Reinhard,
+            Some(ToneMapName::Aces) => ToneMap::Aces,
+            Some(ToneMapName::Hable) => ToneMap::Hable,
+        };
+        DisplayTransform {
+            exposure: self.exposure.unwrap_or(scene.exposure),
+            tone_map,
+        }
+    }
+
+    /// Adaptive sampling settings, given the scene's. `--min-samples` alone
+    /// does not turn adaptive sampling on.
+    pub fn adaptive(&self, scene: Option<AdaptiveSampling>) -> Option<AdaptiveSampling> {
+        let threshold = self.adaptive.or(scene.map(|adaptive| adaptive.threshold))?;
+        let min_samples = self
+            .min_samples
+            .or(scene.map(|adaptive| adaptive.min_samples))
+            .unwrap_or(AdaptiveSampling::DEFAULT_MIN_SAMPLES);
+        Some(AdaptiveSampling {
+            min_samples,
+            threshold,
+        })
+    }
+
+    /// The format to write the image in.
+    pub fn format(&self) -> Format {
+        match self.format {
+            None => Format::from_path(&self.output),
+            Some(FormatName::Png) => Format::Png,
+            Some(FormatName::Exr) => Format::Exr,
+            Some(FormatName::Hdr) => Format::Hdr,
+            Some(FormatName::Pfm) => Format::Pfm,
+        }
+    }
+
+    /// The integrator to render with, given the one the scene asked for.
+    pub fn integrator(&self, scene: IntegratorKind) -> IntegratorKind {
+        let scene_distance = match scene {
+            IntegratorKind::AmbientOcclusion { distance } => Some(distance),
+            _ => None,
+        };
+        let distance = self
+            .occlusion_distance
+            .or(scene_distance)
+            .unwrap_or(DEFAULT_OCCLUSION_DISTANCE);
+        match self.integrator {
+            None => match scene {
+                IntegratorKind::AmbientOcclusion { .. } => {
+                    IntegratorKind::AmbientOcclusion { distance }
+                }
+                _ => scene,
+            },
+            Some(IntegratorName::Path) => IntegratorKind::Path,
+            Some(IntegratorName::AmbientOcclusion) => IntegratorKind::AmbientOcclusion { distance },
+            Some(IntegratorName::Whitted) => IntegratorKind::Whitted,
+            Some(IntegratorName::Normals) => IntegratorKind::Normals,
+            Some(IntegratorName::Depth) => IntegratorKind::Depth,
+            Some(IntegratorName::MaterialIds) => IntegratorKind::MaterialIds,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum BuiltInScene {
+    /// The final scene of the book: hundreds of small random spheres
+    BookCover,
+    /// The book cover scene with motion-blurred bouncing spheres
+    BouncingSpheres,
+    /// Three spheres on a large ground sphere
+    TestWorld,
+    /// Two spheres with a checkered texture
+    CheckeredSpheres,
+    /// A sphere lit by a spherical light against a black background
+    SimpleLight,
+    /// Marble, wood and cloud textured spheres made from Perlin noise
+    PerlinSpheres,
+}
+
+/// Objects in the world, the lights among them and the camera.
+type BuiltScene = (
+    Vec<Box<dyn Hittable + Send + Sync>>,
+    Vec<Box<dyn Hittable + Send + Sync>>,
+    Camera,
+);
+
+impl BuiltInScene {
+    /// Builds the scene, with objects tagged with IDs in the order they are
+    /// created. Each scene numbers its own materials.
+    pub fn build<R: Rng + ?Sized>(self, aspect_ratio: f64, rng: &mut R) -> BuiltScene {
+        let (world, lights, camera) = match self {
+            BuiltInScene::BookCover => (
+                world::book_cover_scene(rng),
+                Vec::new(),
+                world::book_cover_camera(aspect_ratio),
+            ),
+            BuiltInScene::BouncingSpheres => (
+                world::bouncing_spheres(rng),
+                Vec::new(),
+                world::bouncing_spheres_camera(aspect_ratio),
+            ),
+            BuiltInScene::TestWorld => (
+                world::test_world(),
+                Vec::new(),
+                world::test_world_camera(aspect_ratio),
+            ),
+            BuiltInScene::CheckeredSpheres => (
+                world::checkered_spheres(),
+                Vec::new(),
+                world::checkered_spheres_camera(aspect_ratio),
+            ),
+            BuiltInScene::SimpleLight => (
+                world::simple_light(),
+                world::simple_light_sources(),
+                world::simple_light_camera(aspect_ratio),
+            ),
+            BuiltInScene::PerlinSpheres => (
+                world::perlin_spheres(rng),
+                Vec::new(),
+                world::perlin_spheres_camera(aspect_ratio),
+            ),
+        };
+        (tag_objects(world), lights, camera)
+    }
+
+    pub fn background(self) -> Background {
+        match self {
+            BuiltInScene::BookCover
+            | BuiltInScene::BouncingSpheres
+            | BuiltInScene::TestWorld
+            | BuiltInScene::CheckeredSpheres
+            | BuiltInScene::PerlinSpheres => Background::Sky,
+            BuiltInScene::SimpleLight => Background::Solid(Color::default()),
+        }
+    }
+}
+
+fn parse_aspect_ratio(s: &str) -> Result<f64, String> {
+    let ratio = match s.split_once(':') {
+        Some((width, height)) => {
+            let width: f64 = width
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid width `{}`", width))?;
+            let height: f64 = height
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid height `{}`", height))?;
+            width / height
+        }
+        None => s.parse().map_err(|_| format!("invalid ratio `{}`", s))?,
+    };
+    if ratio.is_finite() && ratio > 0.0 {
+        Ok(ratio)
+    } else {
+        Err(format!("`{}` is not a positive ratio", s))
+    }
+}
+
+fn parse_distance(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(distance) if distance.is_finite() && distance > 0.0 => Ok(distance),
+        _ => Err(format!("`{}` is not a positive distance", s)),
+    }
+}
+
+fn parse_threshold(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(threshold) if threshold.is_finite() && threshold > 0.0 => Ok(threshold),
+        _ => Err(format!("`{}` is not a positive error threshold", s)),
+    }
+}
+
+fn parse_exposure(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(exposure) if exposure.is_finite() => Ok(exposure),
+        _ => Err(format!("`{}` is not a number of stops", s)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use ray_tracing_in_one_weekend::ray::Ray;
+    use ray_tracing_in_one_weekend::vec3::{Point3, Vec3};
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_parse_aspect_ratio() {
+        assert_eq!(Ok(2.0), parse_aspect_ratio("2"));
+        assert_eq!(Ok(16.0 / 9.0), parse_aspect_ratio("16:9"));
+        assert!(parse_aspect_ratio("16:0").is_err());
+        assert!(parse_aspect_ratio("-1.5").is_err());
+        assert!(parse_aspect_ratio("wide").is_err());
+    }
+
+    #[test]
+    fn test_parse_arguments() {
+        let cli = Cli::try_parse_from([
+            "rt",
+            "--scene",
+            "test-world",
+            "-w",
+            "400",
+            "-n",
+            "10",
+            "--seed",
+            "7",
+        ])
+        .unwrap();
+        assert_eq!(Some(BuiltInScene::TestWorld), cli.scene);
+        assert_eq!(Some(400), cli.width);
+        assert_eq!(Some(10), cli.samples_per_pixel);
+        assert_eq!(Some(7), cli.seed);
+        assert_eq!(PathBuf::from("image.png"), cli.output);
+        assert_eq!(
+            IntegratorKind::Whitted,
+            cli.integrator(IntegratorKind::Whitted)
+        );
+    }
+
+    #[test]
+    fn test_integrator() {
+        let parse = |args: &[&str]| Cli::try_parse_from(args).unwrap();
+        let scene_ao = IntegratorKind::AmbientOcclusion { distance: 3.0 };
+
+        let cli = parse(&["rt", "--integrator", "normals"]);
+        assert_eq!(IntegratorKind::Normals, cli.integrator(scene_ao));
+
+        let cli = parse(&["rt", "-i", "ambient-occlusion"]);
+        assert_eq!(scene_ao, cli.integrator(scene_ao));
+        assert_eq!(
+            IntegratorKind::AmbientOcclusion { distance: 1.0 },
+            cli.integrator(IntegratorKind::Path)
+        );
+
+        let cli = parse(&["rt", "--occlusion-distance", "0.25"]);
+        assert_eq!(
+            IntegratorKind::AmbientOcclusion { distance: 0.25 },
+            cli.integrator(scene_ao)
+        );
+        assert_eq!(IntegratorKind::Path, cli.integrator(IntegratorKind::Path));
+    }
+
+    #[test]
+    fn test_adaptive() {
+        let parse = |args: &[&str]| Cli::try_parse_from(args).unwrap();
+        let scene = AdaptiveSampling {
+            min_samples: 8,
+            threshold: 0.02,
+        };
+        assert_eq!(None, parse(&["rt"]).adaptive(None));
+        assert_eq!(None, parse(&["rt", "--min-samples", "4"]).adaptive(None));
+        assert_eq!(Some(scene), parse(&["rt"]).adaptive(Some(scene)));
+        assert_eq!(
+            Some(AdaptiveSampling {
+                min_samples: 16,
+                threshold: 0.01,
+            }),
+            parse(&["rt", "--adaptive", "0.01"]).adaptive(None)
+        );
+        assert_eq!(
+            Some(AdaptiveSampling {
+                min_samples: 4,
+                threshold: 0.02,
+            }),
+            parse(&["rt", "--min-samples", "4"]).adaptive(Some(scene))
+        );
+    }
+
+    #[test]
+    fn test_format() {
+        let parse = |args: &[&str]| Cli::try_parse_from(args).unwrap();
+        assert_eq!(Format::Png, parse(&["rt"]).format());
+        assert_eq!(Format::Exr, parse(&["rt", "-o", "render.exr"]).format());
+        assert_eq!(
+            Format::Pfm,
+            parse(&["rt", "-o", "render.exr", "--format", "pfm"]).format()
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let parse = |args: &[&str]| Cli::try_parse_from(args).unwrap();
+        let scene = DisplayTransform {
+            exposure: 1.0,
+            tone_map: ToneMap::Hable,
+        };
+        assert_eq!(scene, parse(&["rt"]).display(scene));
+        assert_eq!(
+            DisplayTransform {
+                exposure: -2.5,
+                tone_map: ToneMap::Reinhard,
+            },
+            parse(&["rt", "--exposure", "-2.5", "--tone-map", "reinhard"]).display(scene)
+        );
+    }
+
+    #[test]
+    fn test_aovs() {
+        let parse = |args: &[&str]| Cli::try_parse_from(args).unwrap();
+        let scene = [Aov::Depth];
+        assert_eq!(vec![Aov::Depth], parse(&["rt"]).aovs(&scene));
+        assert_eq!(
+            vec![Aov::Albedo, Aov::Normal, Aov::ObjectId],
+            parse(&[
+                "rt",
+                "--aov",
+                "albedo,normal",
+                "--aov",
+                "object-id",
+                "--aov",
+                "albedo"
+            ])
+            .aovs(&scene)
+        );
+    }
+
+    #[test]
+    fn test_build_tags_objects_and_materials() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let (world, _, _) = BuiltInScene::TestWorld.build(1.5, &mut rng);
+        let mut ids = |origin: Point3| {
+            let ray = Ray::new(origin, Vec3::new(0.0, 0.0, -1.0));
+            world
+                .hit(&ray, 0.001, f64::INFINITY, &mut rng)
+                .map(|record| (record.object_id, record.material.id()))
+        };
+        assert_eq!(Some((2, 2)), ids(Point3::new(0.0, 0.0, 1.0)));
+        assert_eq!(Some((5, 4)), ids(Point3::new(1.0, 0.0, 1.0)));
+    }
+
+    #[test]
+    fn test_bounds() {
+        let bounds =
+            |args: &[&str], scene: Option<Bounds>| Cli::try_parse_from(args).unwrap().bounds(scene);
+        let scene = Bounds {
+            width: 400,
+            height: 200,
+        };
+        assert_eq!(scene, bounds(&["rt"], Some(scene)).unwrap());
+        assert_eq!(
+            Bounds {
+                width: 100,
+                height: 50
+            },
+            bounds(&["rt", "--width", "100"], Some(scene)).unwrap()
+        );
+        assert_eq!(
+            Bounds {
+                width: 300,
+                height: 100
+            },
+            bounds(&["rt", "-w", "300", "-a", "3"], None).unwrap()
+        );
+        let kind = |args: &[&str], scene| bounds(args, scene).unwrap_err().kind();
+        assert_eq!(
+            ErrorKind::ValueValidation,
+            kind(&["rt", "--width", "3"], Some(scene))
+        );
+        assert_eq!(
+            ErrorKind::ValueValidation,
+            kind(&["rt", "--width", "10", "--aspect-ratio", "8"], None)
+        );
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        let kind = |args: &[&str]| Cli::try_parse_from(args).unwrap_err().kind();
+        assert_eq!(ErrorKind::ValueValidation, kind(&["rt", "--width", "0"]));
+        assert_eq!(ErrorKind::ValueValidation, kind(&["rt", "--width", "1"]));
+        assert_eq!(ErrorKind::InvalidValue, kind(&["rt", "--scene", "nope"]));
+        assert_eq!(
+            ErrorKind::ValueValidation,
+            kind(&["rt", "--exposure", "inf"])
+        );
+        assert_eq!(
+            ErrorKind::ValueValidation,
+            kind(&["rt", "--occlusion-distance", "0"])
+        );
+        assert_eq!(ErrorKind::ValueValidation, kind(&["rt", "--adaptive", "0"]));
+        assert_eq!(
+            ErrorKind::ArgumentConflict,
+            kind(&["rt", "scene.toml", "--scene", "test-world"])
+        );
+    }
+}
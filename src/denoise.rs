@@ -0,0 +1,245 @@
+//! Denoising for quick previews at low sample counts, with an edge-avoiding
+//! à-trous wavelet filter (Dammertz et al., 2010).
+//!
+//! The filter blurs the image with ever wider, sparser kernels, but weighs
+//! each neighbor by how similar its normal, depth and color are, so that
+//! edges stay sharp. Textures are kept by dividing the albedo out before
+//! filtering and multiplying it back in after.
+
+use crate::render::Bounds;
+use crate::vec3::Color;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+/// Per-pixel features of the first surface hit, from the albedo, normal and
+/// depth AOVs, which tell the filter where the edges are.
+pub struct Features<'a> {
+    pub albedo: &'a [Color],
+    pub normal: &'a [Color],
+    pub depth: &'a [Color],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DenoiseParams {
+    /// Number of passes. Each doubles the filter's reach, to 2^(n + 1) pixels.
+    pub iterations: u32,
+    /// How different colors may be and still blend, after mapping them into
+    /// `[0, 1]`. Halved on every pass, as the noise it has to bridge shrinks.
+    pub sigma_color: f64,
+    /// How far apart normals may be and still blend.
+    pub sigma_normal: f64,
+    /// How different albedos may be and still blend. This keeps lights,
+    /// which have no albedo, apart from the surfaces around them.
+    pub sigma_albedo: f64,
+    /// How different depths may be, relative to the depth and per pixel of
+    /// distance, and still blend.
+    pub sigma_depth: f64,
+}
+
+impl Default for DenoiseParams {
+    fn default() -> Self {
+        DenoiseParams {
+            iterations: 3,
+            sigma_color: 1.0,
+            sigma_normal: 0.3,
+            sigma_albedo: 0.1,
+            sigma_depth: 0.02,
+        }
+    }
+}
+
+/// Weights of the B3 spline that every pass spreads over a 5x5 grid.
+const KERNEL: [f64; 5] = [1.0 / 16.0, 1.0 / 4.0, 3.0 / 8.0, 1.0 / 4.0, 1.0 / 16.0];
+
+/// Albedo below this is raised to it before being divided out, so that dark
+/// surfaces and rays that miss do not blow up.
+const MIN_ALBEDO: f64 = 0.01;
+
+/// Denoises the averaged, linear `pixels` of an image, guided by `features`.
+pub fn denoise(
+    pixels: &[Color],
+    bounds: Bounds,
+    features: &Features,
+    params: DenoiseParams,
+) -> Vec<Color> {
+    let albedo: Vec<Color> = features
+        .albedo
+        .iter()
+        .map(|a| {
+            Color::new(
+                a.x.max(MIN_ALBEDO),
+                a.y.max(MIN_ALBEDO),
+                a.z.max(MIN_ALBEDO),
+            )
+        })
+        .collect();
+    let mut irradiance: Vec<Color> = pixels
+        .iter()
+        .zip(albedo.iter())
+        .map(|(c, a)| Color::new(c.x / a.x, c.y / a.y, c.z / a.z))
+        .collect();
+
+    for i in 0..params.iterations {
+        irradiance = filter_pass(&irradiance, bounds, features, params, 1 << i);
+    }
+    irradiance
+        .into_iter()
+        .zip(albedo)
+        .map(|(e, a)| e * a)
+        .collect()
+}
+
+/// Maps each channel into `[0, 1)`, so that color differences mean the same
+/// in dim and bright parts of an HDR image.
+fn compress(c: Color) -> Color {
+    Color::new(c.x / (1.0 + c.x), c.y / (1.0 + c.y), c.z / (1.0 + c.z))
+}
+
+/// One à-trous pass, with taps `step` pixels apart.
+fn filter_pass(
+    image: &[Color],
+    bounds: Bounds,
+    features: &Features,
+    params: DenoiseParams,
+    step: i64,
+) -> Vec<Color> {
+    let (width, height) = (bounds.width as i64, bounds.height as i64);
+    let sigma_color = params.sigma_color / step as f64;
+    (0..width * height)
+        .into_par_iter()
+        .map(|p| {
+            let (x, y) = (p % width, p / width);
+            let p = p as usize;
+            let color = compress(image[p]);
+            let depth = features.depth[p].x;
+
+            let mut sum = Color::default();
+            let mut total_weight = 0.0;
+            for (j, ky) in KERNEL.iter().enumerate() {
+                let qy = y + (j as i64 - 2) * step;
+                if qy < 0 || qy >= height {
+                    continue;
+                }
+                for (i, kx) in KERNEL.iter().enumerate() {
+                    let qx = x + (i as i64 - 2) * step;
+                    if qx < 0 || qx >= width {
+                        continue;
+                    }
+                    let q = (qy * width + qx) as usize;
+
+                    let color_distance = (compress(image[q]) - color).length_squared();
+                    let normal_distance =
+                        (features.normal[q] - features.normal[p]).length_squared();
+                    let albedo_distance =
+                        (features.albedo[q] - features.albedo[p]).length_squared();
+                    let depth_distance = (features.depth[q].x - depth).abs()
+                        / (params.sigma_depth * step as f64 * depth.max(features.depth[q].x))
+                            .max(f64::MIN_POSITIVE);
+                    let weight = kx
+                        * ky
+                        * (-color_distance / (sigma_color * sigma_color)
+                            - normal_distance / (params.sigma_normal * params.sigma_normal)
+                            - albedo_distance / (params.sigma_albedo * params.sigma_albedo)
+                            - depth_distance * depth_distance)
+                            .exp();
+                    sum += weight * image[q];
+                    total_weight += weight;
+                }
+            }
+            sum / total_weight
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    const BOUNDS: Bounds = Bounds {
+        width: 64,
+        height: 32,
+    };
+
+    fn mean_squared_error(a: &[Color], b: &[Color]) -> f64 {
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| (*a - *b).length_squared())
+            .sum::<f64>()
+            / a.len() as f64
+    }
+
+    #[test]
+    fn test_denoise_keeps_edges() {
+        // Two walls meeting at x = 32, facing different ways and lit
+        // differently, each with uniform noise.
+        let left = |x: u32| x < BOUNDS.width / 2;
+        let pixel_count = (BOUNDS.width * BOUNDS.height) as usize;
+        let clean: Vec<Color> = (0..pixel_count as u32)
+            .map(|p| {
+                if left(p % BOUNDS.width) {
+                    Color::new(0.2, 0.2, 0.2)
+                } else {
+                    Color::new(2.0, 1.0, 0.5)
+                }
+            })
+            .collect();
+        let normal: Vec<Color> = (0..pixel_count as u32)
+            .map(|p| {
+                if left(p % BOUNDS.width) {
+                    Color::new(1.0, 0.0, 0.0)
+                } else {
+                    Color::new(0.0, 0.0, 1.0)
+                }
+            })
+            .collect();
+        let albedo = vec![Color::new(0.5, 0.5, 0.5); pixel_count];
+        let depth = vec![Color::new(4.0, 4.0, 4.0); pixel_count];
+        let features = Features {
+            albedo: &albedo,
+            normal: &normal,
+            depth: &depth,
+        };
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let noisy: Vec<Color> = clean
+            .iter()
+            .map(|&c| c * (2.0 * rng.gen::<f64>()))
+            .collect();
+        let denoised = denoise(&noisy, BOUNDS, &features, DenoiseParams::default());
+
+        let before = mean_squared_error(&noisy, &clean);
+        let after = mean_squared_error(&denoised, &clean);
+        assert!(after < before / 10.0, "{} -> {}", before, after);
+
+        // Neither wall bleeds into the other.
+        let row = (BOUNDS.height / 2 * BOUNDS.width) as usize;
+        let edge = BOUNDS.width as usize / 2;
+        assert!((denoised[row + edge - 1].x - 0.2).abs() < 0.1);
+        assert!((denoised[row + edge].x - 2.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_denoise_keeps_textures() {
+        // A noise-free checkerboard in the albedo comes through unchanged.
+        let pixel_count = (BOUNDS.width * BOUNDS.height) as usize;
+        let albedo: Vec<Color> = (0..pixel_count as u32)
+            .map(|p| {
+                if (p % BOUNDS.width + p / BOUNDS.width).is_multiple_of(2) {
+                    Color::new(0.9, 0.9, 0.9)
+                } else {
+                    Color::new(0.1, 0.1, 0.1)
+                }
+            })
+            .collect();
+        let normal = vec![Color::new(0.0, 1.0, 0.0); pixel_count];
+        let depth = vec![Color::new(1.0, 1.0, 1.0); pixel_count];
+        let features = Features {
+            albedo: &albedo,
+            normal: &normal,
+            depth: &depth,
+        };
+        let denoised = denoise(&albedo, BOUNDS, &features, DenoiseParams::default());
+        assert!(mean_squared_error(&denoised, &albedo) < 1e-20);
+    }
+}
@@ -1,15 +1,33 @@
+use crate::aabb::Aabb;
 use crate::material::Material;
 use crate::ray::Ray;
 use crate::vec3::{Point3, Vec3};
+use rand::{Rng, RngCore};
 
+pub mod bvh;
+pub mod constant_medium;
+pub mod cuboid;
+pub mod disk;
+pub mod instance;
+pub mod mesh;
+pub mod moving_sphere;
+pub mod plane;
+pub mod quad;
 pub mod sphere;
+pub mod tagged;
+pub mod triangle;
 
 pub struct HitRecord<'a> {
     pub p: Point3,
     pub normal: Vec3,
     pub material: &'a dyn Material,
     pub t: f64,
+    pub u: f64,
+    pub v: f64,
     pub front_face: bool,
+    /// Stable ID of the top-level object that was hit, or 0 if it has none.
+    /// Set by `TaggedHittable`.
+    pub object_id: u32,
 }
 
 impl<'a> HitRecord<'a> {
@@ -18,6 +36,7 @@ impl<'a> HitRecord<'a> {
         normal: Vec3,
         material: &'a dyn Material,
         t: f64,
+        (u, v): (f64, f64),
         front_face: bool,
     ) -> Self {
         HitRecord {
@@ -25,7 +44,10 @@ impl<'a> HitRecord<'a> {
             normal,
             material,
             t,
+            u,
+            v,
             front_face,
+            object_id: 0,
         }
     }
 
@@ -36,6 +58,7 @@ impl<'a> HitRecord<'a> {
         material: &'a dyn Material,
         point: Point3,
         t: f64,
+        uv: (f64, f64),
     ) -> Self {
         let front_face = ray.direction.dot(outward_normal) < 0.0;
         let normal = if front_face {
@@ -43,23 +66,106 @@ impl<'a> HitRecord<'a> {
         } else {
             -outward_normal
         };
-        HitRecord::new(point, normal, material, t, front_face)
+        HitRecord::new(point, normal, material, t, uv, front_face)
     }
 }
 
 pub trait Hittable {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord>;
+    fn hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rng: &mut dyn RngCore,
+    ) -> Option<HitRecord<'_>>;
+
+    /// Axis-aligned box enclosing the object, or `None` if it is unbounded.
+    fn bounding_box(&self) -> Option<Aabb>;
+
+    /// Density, per unit solid angle, with which `random` generates `direction`
+    /// from `origin`. Objects that cannot be sampled as lights return zero.
+    fn pdf_value(&self, _origin: Point3, _direction: Vec3) -> f64 {
+        0.0
+    }
+
+    /// A random direction from `origin` towards the object, for sampling it as
+    /// a light.
+    fn random(&self, _origin: Point3, _rng: &mut dyn RngCore) -> Vec3 {
+        Vec3::new(1.0, 0.0, 0.0)
+    }
 }
 
 impl<T: AsRef<[Box<dyn Hittable + Sync + Send>]>> Hittable for T {
-    fn hit(&self, ray: &Ray, t_min: f64, mut t_max: f64) -> Option<HitRecord> {
+    fn hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        mut t_max: f64,
+        rng: &mut dyn RngCore,
+    ) -> Option<HitRecord<'_>> {
         let mut closest_so_far: Option<HitRecord> = None;
         for hittable in self.as_ref().iter() {
-            if let Some(hit_record) = hittable.hit(ray, t_min, t_max) {
+            if let Some(hit_record) = hittable.hit(ray, t_min, t_max, rng) {
                 t_max = hit_record.t;
                 closest_so_far = Some(hit_record);
             }
         }
         closest_so_far
     }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let mut objects = self.as_ref().iter();
+        let first = objects.next()?.bounding_box()?;
+        objects.try_fold(first, |output_box, hittable| {
+            Some(Aabb::surrounding_box(output_box, hittable.bounding_box()?))
+        })
+    }
+
+    /// Picks one object uniformly and samples it, so the density is the
+    /// average of the objects' densities.
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let objects = self.as_ref();
+        if objects.is_empty() {
+            return 0.0;
+        }
+        let sum: f64 = objects
+            .iter()
+            .map(|object| object.pdf_value(origin, direction))
+            .sum();
+        sum / objects.len() as f64
+    }
+
+    fn random(&self, origin: Point3, rng: &mut dyn RngCore) -> Vec3 {
+        let objects = self.as_ref();
+        if objects.is_empty() {
+            return Vec3::new(1.0, 0.0, 0.0);
+        }
+        objects[rng.gen_range(0, objects.len())].random(origin, rng)
+    }
+}
+
+/// Lets a boxed object be wrapped again, e.g. tagged, without first putting it
+/// in a list of its own.
+impl Hittable for Box<dyn Hittable + Send + Sync> {
+    fn hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rng: &mut dyn RngCore,
+    ) -> Option<HitRecord<'_>> {
+        (**self).hit(ray, t_min, t_max, rng)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        (**self).bounding_box()
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        (**self).pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: Point3, rng: &mut dyn RngCore) -> Vec3 {
+        (**self).random(origin, rng)
+    }
 }
@@ -0,0 +1,283 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::ray::Ray;
+use rand::RngCore;
+
+/// Cost of visiting an interior node, relative to intersecting one primitive.
+const TRAVERSAL_COST: f64 = 0.125;
+/// Nodes with more primitives than this are always split.
+const MAX_LEAF_SIZE: usize = 4;
+
+type BoundedObject = (Aabb, Box<dyn Hittable + Send + Sync>);
+
+/// Bounding volume hierarchy built with the surface area heuristic.
+pub struct BvhNode {
+    bbox: Aabb,
+    contents: Contents,
+    /// Objects without a bounding box, which cannot go in the hierarchy and
+    /// are tested alongside it instead. Only the root has any.
+    unbounded: Vec<Box<dyn Hittable + Send + Sync>>,
+}
+
+enum Contents {
+    Leaf(Vec<Box<dyn Hittable + Send + Sync>>),
+    Branch(Box<BvhNode>, Box<BvhNode>),
+}
+
+impl BvhNode {
+    /// Builds a hierarchy over the bounded `objects`. Unbounded ones, such as
+    /// planes, are kept in a list beside it.
+    pub fn new(objects: Vec<Box<dyn Hittable + Send + Sync>>) -> Self {
+        let mut items = Vec::new();
+        let mut unbounded = Vec::new();
+        for object in objects {
+            match object.bounding_box() {
+                Some(bbox) => items.push((bbox, object)),
+                None => unbounded.push(object),
+            }
+        }
+
+        let root = if items.is_empty() {
+            BvhNode {
+                bbox: Aabb::new(Default::default(), Default::default()),
+                contents: Contents::Leaf(Vec::new()),
+                unbounded: Vec::new(),
+            }
+        } else {
+            BvhNode::build(items)
+        };
+        BvhNode { unbounded, ..root }
+    }
+
+    fn build(mut items: Vec<BoundedObject>) -> Self {
+        let bbox = items
+            .iter()
+            .map(|(bbox, _)| *bbox)
+            .reduce(Aabb::surrounding_box)
+            .expect("BvhNode::build called with no objects");
+
+        let n = items.len();
+        let split = if n == 1 {
+            None
+        } else {
+            let (axis, index, cost) = BvhNode::best_split(&mut items, &bbox);
+            let leaf_cost = n as f64;
+            if n > MAX_LEAF_SIZE || cost < leaf_cost {
+                Some((axis, index))
+            } else {
+                None
+            }
+        };
+
+        let contents = match split {
+            None => Contents::Leaf(items.into_iter().map(|(_, object)| object).collect()),
+            Some((axis, index)) => {
+                sort_by_centroid(&mut items, axis);
+                let right = items.split_off(index);
+                Contents::Branch(
+                    Box::new(BvhNode::build(items)),
+                    Box::new(BvhNode::build(right)),
+                )
+            }
+        };
+
+        BvhNode {
+            bbox,
+            contents,
+            unbounded: Vec::new(),
+        }
+    }
+
+    /// Sweeps every axis and returns the `(axis, index, cost)` of the cheapest
+    /// partition, where `index` is the first object placed in the right child.
+    fn best_split(items: &mut [BoundedObject], bbox: &Aabb) -> (usize, usize, f64) {
+        let n = items.len();
+        let parent_area = bbox.surface_area();
+
+        // Degenerate nodes (e.g. every object at one point) give SAH nothing to
+        // work with, so fall back to a median split.
+        if parent_area <= 0.0 {
+            return (0, n / 2, TRAVERSAL_COST + n as f64);
+        }
+
+        let mut best = (0, n / 2, f64::INFINITY);
+        let mut right_areas = vec![0.0; n];
+        for axis in 0..3 {
+            sort_by_centroid(items, axis);
+
+            let mut right_box = items[n - 1].0;
+            for i in (1..n).rev() {
+                right_box = Aabb::surrounding_box(right_box, items[i].0);
+                right_areas[i] = right_box.surface_area();
+            }
+
+            let mut left_box = items[0].0;
+            for i in 1..n {
+                left_box = Aabb::surrounding_box(left_box, items[i - 1].0);
+                let cost = TRAVERSAL_COST
+                    + (left_box.surface_area() * i as f64 + right_areas[i] * (n - i) as f64)
+                        / parent_area;
+                if cost < best.2 {
+                    best = (axis, i, cost);
+                }
+            }
+        }
+        best
+    }
+}
+
+fn sort_by_centroid(items: &mut [BoundedObject], axis: usize) {
+    items.sort_by(|(a, _), (b, _)| a.centroid()[axis].total_cmp(&b.centroid()[axis]));
+}
+
+impl Hittable for BvhNode {
+    fn hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rng: &mut dyn RngCore,
+    ) -> Option<HitRecord<'_>> {
+        let hit_tree = if self.bbox.hit(ray, t_min, t_max) {
+            match &self.contents {
+                Contents::Leaf(objects) => objects.hit(ray, t_min, t_max, rng),
+                Contents::Branch(left, right) => {
+                    let hit_left = left.hit(ray, t_min, t_max, rng);
+                    let t_max = hit_left.as_ref().map_or(t_max, |record| record.t);
+                    right.hit(ray, t_min, t_max, rng).or(hit_left)
+                }
+            }
+        } else {
+            None
+        };
+        let t_max = hit_tree.as_ref().map_or(t_max, |record| record.t);
+        self.unbounded.hit(ray, t_min, t_max, rng).or(hit_tree)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        if !self.unbounded.is_empty() {
+            return None;
+        }
+        match &self.contents {
+            Contents::Leaf(objects) if objects.is_empty() => None,
+            _ => Some(self.bbox),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable::plane::Plane;
+    use crate::hittable::sphere::Sphere;
+    use crate::material::lambertian::Lambertian;
+    use crate::vec3::{Color, Point3, Vec3};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn build_spheres(spheres: &[(Point3, f64)]) -> Vec<Box<dyn Hittable + Send + Sync>> {
+        spheres
+            .iter()
+            .map(|&(center, radius)| {
+                let sphere: Box<dyn Hittable + Send + Sync> = Box::new(Sphere::new(
+                    center,
+                    radius,
+                    Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+                ));
+                sphere
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_bvh_matches_linear_list() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let spheres: Vec<(Point3, f64)> = (0..200)
+            .map(|_| {
+                (
+                    Point3::random(&mut rng, -10.0, 10.0),
+                    rng.gen_range(0.1, 1.5),
+                )
+            })
+            .collect();
+        let list = build_spheres(&spheres);
+        let bvh = BvhNode::new(build_spheres(&spheres));
+
+        assert_eq!(list.bounding_box(), bvh.bounding_box());
+
+        for _ in 0..2000 {
+            let ray = Ray::new(
+                Point3::random(&mut rng, -15.0, 15.0),
+                Vec3::random_unit_vector(&mut rng),
+            );
+            let expected = list.hit(&ray, 0.001, f64::INFINITY, &mut rng);
+            let actual = bvh.hit(&ray, 0.001, f64::INFINITY, &mut rng);
+            match (expected, actual) {
+                (None, None) => {}
+                (Some(exp), Some(act)) => {
+                    assert!(approx_eq!(f64, exp.t, act.t, ulps = 2));
+                    assert_eq!(exp.p, act.p);
+                    assert_eq!(exp.normal, act.normal);
+                }
+                (exp, act) => panic!(
+                    "list hit: {}, bvh hit: {} for {:?}",
+                    exp.is_some(),
+                    act.is_some(),
+                    ray
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn test_bvh_single_object() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let bvh = BvhNode::new(build_spheres(&[(Point3::new(0.0, 0.0, -1.0), 0.5)]));
+        let ray = Ray::new(Point3::default(), Vec3::new(0.0, 0.0, -1.0));
+        let record = bvh.hit(&ray, 0.001, f64::INFINITY, &mut rng).unwrap();
+        assert!(approx_eq!(f64, record.t, 0.5, ulps = 2));
+    }
+
+    #[test]
+    fn test_empty_bvh() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let bvh = BvhNode::new(Vec::new());
+        let ray = Ray::new(Point3::default(), Vec3::new(0.0, 0.0, -1.0));
+        assert!(bvh.hit(&ray, 0.001, f64::INFINITY, &mut rng).is_none());
+        assert!(bvh.bounding_box().is_none());
+    }
+
+    #[test]
+    fn test_bvh_with_unbounded_objects() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut objects = build_spheres(&[(Point3::new(0.0, 0.0, -2.0), 0.5)]);
+        objects.push(Box::new(Plane::new(
+            Point3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        )));
+        let bvh = BvhNode::new(objects);
+        assert!(bvh.bounding_box().is_none());
+
+        let mut t = |direction: Vec3| {
+            let ray = Ray::new(Point3::default(), direction);
+            bvh.hit(&ray, 0.001, f64::INFINITY, &mut rng)
+                .map(|record| record.t)
+        };
+        // The sphere, in front of the plane.
+        assert!(approx_eq!(
+            f64,
+            1.5,
+            t(Vec3::new(0.0, 0.0, -1.0)).unwrap(),
+            ulps = 2
+        ));
+        // The plane, missing the hierarchy entirely.
+        assert!(approx_eq!(
+            f64,
+            1.0,
+            t(Vec3::new(0.0, -1.0, 0.0)).unwrap(),
+            ulps = 2
+        ));
+        assert_eq!(None, t(Vec3::new(0.0, 1.0, 0.0)));
+    }
+}
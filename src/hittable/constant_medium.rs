@@ -0,0 +1,176 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::material::isotropic::Isotropic;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::vec3::{Color, Vec3};
+use rand::{Rng, RngCore};
+
+/// A volume of uniform density, such as smoke or fog, filling a closed
+/// `boundary`. Rays passing through it scatter at an exponentially distributed
+/// distance, off the `phase_function` material.
+///
+/// The boundary must be convex: only the first stretch of the ray inside it is
+/// considered.
+pub struct ConstantMedium<B: Hittable, M: Material = Isotropic> {
+    pub boundary: B,
+    pub phase_function: M,
+    neg_inv_density: f64,
+}
+
+impl<B: Hittable> ConstantMedium<B> {
+    /// A medium that scatters light isotropically, tinted by `albedo`.
+    pub fn new(boundary: B, density: f64, albedo: Color) -> Self {
+        ConstantMedium::with_phase_function(boundary, density, Isotropic::new(albedo))
+    }
+}
+
+impl<B: Hittable, M: Material> ConstantMedium<B, M> {
+    pub fn with_phase_function(boundary: B, density: f64, phase_function: M) -> Self {
+        assert!(density > 0.0, "Medium density must be positive");
+        ConstantMedium {
+            boundary,
+            phase_function,
+            neg_inv_density: -1.0 / density,
+        }
+    }
+}
+
+impl<B: Hittable, M: Material> Hittable for ConstantMedium<B, M> {
+    fn hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rng: &mut dyn RngCore,
+    ) -> Option<HitRecord<'_>> {
+        // Find where the ray enters and leaves the boundary, even if it starts
+        // inside.
+        let enter = self
+            .boundary
+            .hit(ray, f64::NEG_INFINITY, f64::INFINITY, rng)?
+            .t;
+        let exit = self
+            .boundary
+            .hit(ray, enter + 0.0001, f64::INFINITY, rng)?
+            .t;
+
+        let enter = enter.max(t_min).max(0.0);
+        let exit = exit.min(t_max);
+        if enter >= exit {
+            return None;
+        }
+
+        let ray_length = ray.direction.length();
+        let distance_inside = (exit - enter) * ray_length;
+        let hit_distance = self.neg_inv_density * rng.gen::<f64>().ln();
+        if hit_distance > distance_inside {
+            return None;
+        }
+
+        let t = enter + hit_distance / ray_length;
+        // The normal and face are arbitrary; the phase function ignores them.
+        Some(HitRecord::new(
+            ray.at(t),
+            Vec3::new(1.0, 0.0, 0.0),
+            &self.phase_function,
+            t,
+            (0.0, 0.0),
+            true,
+        ))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.boundary.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable::sphere::Sphere;
+    use crate::vec3::Point3;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn fog(density: f64) -> ConstantMedium<Sphere<Isotropic>> {
+        let boundary = Sphere::new(
+            Point3::default(),
+            1.0,
+            Isotropic::new(Color::new(1.0, 1.0, 1.0)),
+        );
+        ConstantMedium::new(boundary, density, Color::new(0.5, 0.5, 0.5))
+    }
+
+    #[test]
+    fn test_hits_stay_inside_boundary() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let fog = fog(5.0);
+        let ray = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        for _ in 0..100 {
+            if let Some(record) = fog.hit(&ray, 0.001, f64::INFINITY, &mut rng) {
+                assert!(record.t >= 4.0 && record.t <= 6.0, "t = {}", record.t);
+            }
+        }
+
+        let miss = Ray::new(Point3::new(2.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(fog.hit(&miss, 0.001, f64::INFINITY, &mut rng).is_none());
+    }
+
+    #[test]
+    fn test_transmittance_follows_density() {
+        let mut rng = StdRng::seed_from_u64(0);
+        // Through a diameter of 2, a fraction exp(-density * 2) passes unscattered.
+        let fog = fog(0.5);
+        let trials = 20_000;
+        let ray = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -2.0));
+        let passed = (0..trials)
+            .filter(|_| fog.hit(&ray, 0.001, f64::INFINITY, &mut rng).is_none())
+            .count();
+        let fraction = passed as f64 / trials as f64;
+        assert!((fraction - (-1.0f64).exp()).abs() < 0.02, "{}", fraction);
+    }
+
+    #[test]
+    fn test_seed_decides_hit() {
+        let fog = fog(1.0);
+        let ray = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let t = |seed: u64| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            fog.hit(&ray, 0.001, f64::INFINITY, &mut rng)
+                .map(|record| record.t)
+        };
+        assert_eq!(t(1), t(1));
+        assert_ne!(t(1), t(2));
+    }
+
+    #[test]
+    fn test_media_along_a_ray_are_independent() {
+        let mut rng = StdRng::seed_from_u64(0);
+        // Two identical fogs, one behind the other. Sharing a random number,
+        // each would scatter the ray the same distance past its entry point.
+        let near = fog(1000.0);
+        let far = ConstantMedium::new(
+            Sphere::new(
+                Point3::new(0.0, 0.0, -3.0),
+                1.0,
+                Isotropic::new(Color::new(1.0, 1.0, 1.0)),
+            ),
+            1000.0,
+            Color::new(0.5, 0.5, 0.5),
+        );
+        let ray = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let near_depth = near.hit(&ray, 0.001, f64::INFINITY, &mut rng).unwrap().t - 4.0;
+        let far_depth = far.hit(&ray, 0.001, f64::INFINITY, &mut rng).unwrap().t - 7.0;
+        assert!((near_depth - far_depth).abs() > 1e-6);
+    }
+
+    #[test]
+    fn test_ray_starting_inside() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let fog = fog(1000.0);
+        let ray = Ray::new(Point3::default(), Vec3::new(0.0, 1.0, 0.0));
+        let record = fog.hit(&ray, 0.001, f64::INFINITY, &mut rng).unwrap();
+        assert!(record.t < 1.0);
+    }
+}
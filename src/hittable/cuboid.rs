@@ -0,0 +1,101 @@
+use crate::aabb::Aabb;
+use crate::hittable::quad::Quad;
+use crate::hittable::{HitRecord, Hittable};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use rand::RngCore;
+use std::sync::Arc;
+
+/// An axis-aligned box made of six outward-facing quads sharing one material.
+/// Rotate it by wrapping it in an `Instance`.
+pub struct Cuboid<M: Material> {
+    sides: [Quad<Arc<M>>; 6],
+    bbox: Aabb,
+}
+
+impl<M: Material> Cuboid<M> {
+    /// The box with opposite corners `a` and `b`.
+    pub fn new(a: Point3, b: Point3, material: M) -> Self {
+        let min = Point3::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z));
+        let max = Point3::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z));
+        let dx = Vec3::new(max.x - min.x, 0.0, 0.0);
+        let dy = Vec3::new(0.0, max.y - min.y, 0.0);
+        let dz = Vec3::new(0.0, 0.0, max.z - min.z);
+
+        let material = Arc::new(material);
+        let side = |q: Point3, u: Vec3, v: Vec3| Quad::new(q, u, v, Arc::clone(&material));
+        let sides = [
+            side(Point3::new(min.x, min.y, max.z), dx, dy), // front
+            side(Point3::new(max.x, min.y, max.z), -dz, dy), // right
+            side(Point3::new(max.x, min.y, min.z), -dx, dy), // back
+            side(Point3::new(min.x, min.y, min.z), dz, dy), // left
+            side(Point3::new(min.x, max.y, max.z), dx, -dz), // top
+            side(Point3::new(min.x, min.y, min.z), dx, dz), // bottom
+        ];
+
+        Cuboid {
+            sides,
+            bbox: Aabb::new(min, max),
+        }
+    }
+}
+
+impl<M: Material> Hittable for Cuboid<M> {
+    fn hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        mut t_max: f64,
+        rng: &mut dyn RngCore,
+    ) -> Option<HitRecord<'_>> {
+        let mut closest = None;
+        for side in &self.sides {
+            if let Some(record) = side.hit(ray, t_min, t_max, rng) {
+                t_max = record.t;
+                closest = Some(record);
+            }
+        }
+        closest
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bbox)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::lambertian::Lambertian;
+    use crate::vec3::Color;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_normals_face_outwards() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let cuboid = Cuboid::new(
+            Point3::new(1.0, 1.0, 1.0),
+            Point3::new(-1.0, -1.0, -1.0),
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        );
+        let axes = [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        ];
+        for axis in axes.iter().flat_map(|&a| [a, -a]) {
+            let ray = Ray::new(5.0 * axis, -axis);
+            let record = cuboid.hit(&ray, 0.001, f64::INFINITY, &mut rng).unwrap();
+            assert!(approx_eq!(f64, record.t, 4.0, ulps = 2), "{:?}", axis);
+            assert!(record.front_face, "{:?}", axis);
+            assert_eq!(axis, record.normal);
+        }
+
+        // From inside, the far wall is hit from behind.
+        let ray = Ray::new(Point3::default(), Vec3::new(0.0, 0.0, 1.0));
+        let record = cuboid.hit(&ray, 0.001, f64::INFINITY, &mut rng).unwrap();
+        assert!(!record.front_face);
+    }
+}
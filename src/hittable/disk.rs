@@ -0,0 +1,123 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::material::Material;
+use crate::onb::Onb;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use rand::RngCore;
+
+/// Thickness given to the bounding box of an axis-aligned disk.
+const BOX_PADDING: f64 = 1e-4;
+
+/// A flat circular disk facing along `normal`. Texture coordinates map the
+/// square around the disk onto `[0, 1]`, with the center at `(0.5, 0.5)`.
+pub struct Disk<M: Material> {
+    center: Point3,
+    radius: f64,
+    frame: Onb,
+    material: M,
+}
+
+impl<M: Material> Disk<M> {
+    pub fn new(center: Point3, normal: Vec3, radius: f64, material: M) -> Self {
+        assert!(
+            normal.length_squared() > 0.0,
+            "Disk normal must be non-zero"
+        );
+        Disk {
+            center,
+            radius,
+            frame: Onb::new(normal),
+            material,
+        }
+    }
+}
+
+impl<M: Material> Hittable for Disk<M> {
+    fn hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        _rng: &mut dyn RngCore,
+    ) -> Option<HitRecord<'_>> {
+        let normal = self.frame.w;
+        let denom = normal.dot(ray.direction);
+        if denom.abs() < 1e-12 {
+            return None;
+        }
+
+        let t = normal.dot(self.center - ray.origin) / denom;
+        if t < t_min || t_max < t {
+            return None;
+        }
+
+        let point = ray.at(t);
+        let offset = point - self.center;
+        if offset.length_squared() > self.radius * self.radius {
+            return None;
+        }
+
+        let scale = 0.5 / self.radius;
+        Some(HitRecord::build_with_face_normal(
+            *ray,
+            normal,
+            &self.material,
+            point,
+            t,
+            (
+                0.5 + scale * offset.dot(self.frame.u),
+                0.5 + scale * offset.dot(self.frame.v),
+            ),
+        ))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // Along each axis the disk reaches radius * sin(angle to the normal).
+        let n = self.frame.w;
+        let extent = |c: f64| self.radius * (1.0 - c * c).max(0.0).sqrt();
+        let half = Vec3::new(extent(n.x), extent(n.y), extent(n.z));
+        Some(Aabb::new(self.center - half, self.center + half).pad(BOX_PADDING))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::lambertian::Lambertian;
+    use crate::vec3::Color;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn disk() -> Disk<Lambertian> {
+        Disk::new(
+            Point3::new(0.0, 0.0, -2.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            1.0,
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        )
+    }
+
+    #[test]
+    fn test_hit_inside_radius_only() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let disk = disk();
+        let center = Ray::new(Point3::default(), Vec3::new(0.0, 0.0, -1.0));
+        let record = disk.hit(&center, 0.001, f64::INFINITY, &mut rng).unwrap();
+        assert!(approx_eq!(f64, record.t, 2.0, ulps = 2));
+        assert_eq!(Vec3::new(0.0, 0.0, 1.0), record.normal);
+        assert!(approx_eq!(f64, record.u, 0.5, epsilon = 1e-12));
+        assert!(approx_eq!(f64, record.v, 0.5, epsilon = 1e-12));
+
+        // (0.8, 0.8) is inside the bounding square but outside the circle.
+        let corner = Ray::new(Point3::new(0.8, 0.8, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(disk.hit(&corner, 0.001, f64::INFINITY, &mut rng).is_none());
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let bbox = disk().bounding_box().unwrap();
+        assert_eq!(Point3::new(-1.0, -1.0, -2.0 - 0.5e-4), bbox.minimum);
+        assert_eq!(Point3::new(1.0, 1.0, -2.0 + 0.5e-4), bbox.maximum);
+    }
+}
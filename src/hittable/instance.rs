@@ -0,0 +1,123 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::ray::Ray;
+use crate::transform::Transform;
+use rand::RngCore;
+use std::sync::Arc;
+
+/// Places an object in the world with an affine transform. Rays are moved into
+/// the object's own space to be intersected, and the hit point and normal are
+/// moved back out. The object is shared, so one mesh can appear in many poses.
+pub struct Instance {
+    object: Arc<dyn Hittable + Send + Sync>,
+    transform: Transform,
+}
+
+impl Instance {
+    pub fn new<H: Hittable + Send + Sync + 'static>(object: H, transform: Transform) -> Self {
+        Instance::shared(Arc::new(object), transform)
+    }
+
+    /// An instance of an object that other instances may also use.
+    pub fn shared(object: Arc<dyn Hittable + Send + Sync>, transform: Transform) -> Self {
+        Instance { object, transform }
+    }
+
+    pub fn transform(&self) -> Transform {
+        self.transform
+    }
+}
+
+impl Hittable for Instance {
+    fn hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rng: &mut dyn RngCore,
+    ) -> Option<HitRecord<'_>> {
+        let object_ray = self.transform.inverse().ray(ray);
+        let mut record = self.object.hit(&object_ray, t_min, t_max, rng)?;
+
+        // The object space normal already faces against the object space ray,
+        // and the inverse transpose preserves that, so `front_face` still holds.
+        record.p = self.transform.point(record.p);
+        record.normal = self.transform.normal(record.normal).unit_vector();
+        Some(record)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.object
+            .bounding_box()
+            .map(|bbox| self.transform.bounding_box(bbox))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable::sphere::Sphere;
+    use crate::material::lambertian::Lambertian;
+    use crate::vec3::{Color, Point3, Vec3};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn unit_sphere() -> Arc<dyn Hittable + Send + Sync> {
+        Arc::new(Sphere::new(
+            Point3::default(),
+            1.0,
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        ))
+    }
+
+    #[test]
+    fn test_translated_instances_share_object() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let sphere = unit_sphere();
+        let left = Instance::shared(
+            Arc::clone(&sphere),
+            Transform::translation(Vec3::new(-3.0, 0.0, 0.0)),
+        );
+        let right = Instance::shared(sphere, Transform::translation(Vec3::new(3.0, 0.0, 0.0)));
+
+        let ray = Ray::new(Point3::new(3.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(left.hit(&ray, 0.001, f64::INFINITY, &mut rng).is_none());
+        let record = right.hit(&ray, 0.001, f64::INFINITY, &mut rng).unwrap();
+        assert!(approx_eq!(f64, record.t, 4.0, ulps = 2));
+        assert_eq!(Point3::new(3.0, 0.0, 1.0), record.p);
+        assert_eq!(Vec3::new(0.0, 0.0, 1.0), record.normal);
+    }
+
+    #[test]
+    fn test_scaled_instance() {
+        let mut rng = StdRng::seed_from_u64(0);
+        // Squash the sphere into an ellipsoid twice as wide as it is tall.
+        let ellipsoid =
+            Instance::shared(unit_sphere(), Transform::scaling(Vec3::new(2.0, 1.0, 1.0)));
+
+        let from_side = Ray::new(Point3::new(5.0, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        let record = ellipsoid
+            .hit(&from_side, 0.001, f64::INFINITY, &mut rng)
+            .unwrap();
+        assert!(approx_eq!(f64, record.t, 3.0, ulps = 2));
+
+        // On the ellipsoid x^2/4 + y^2 = 1 the normal at (sqrt(2), sqrt(1/2))
+        // is proportional to (x/4, y). A ray arriving along it hits head on.
+        let p = Point3::new(2.0f64.sqrt(), 0.5f64.sqrt(), 0.0);
+        let expected = Vec3::new(p.x / 4.0, p.y, 0.0).unit_vector();
+        let ray = Ray::new(p + 2.0 * expected, -expected);
+        let record = ellipsoid.hit(&ray, 0.001, f64::INFINITY, &mut rng).unwrap();
+        assert!(record.front_face);
+        assert!(approx_eq!(f64, record.t, 2.0, epsilon = 1e-9));
+        assert!(approx_eq!(
+            f64,
+            record.normal.dot(expected),
+            1.0,
+            epsilon = 1e-9
+        ));
+
+        let bbox = ellipsoid.bounding_box().unwrap();
+        assert_eq!(Point3::new(-2.0, -1.0, -1.0), bbox.minimum);
+        assert_eq!(Point3::new(2.0, 1.0, 1.0), bbox.maximum);
+    }
+}
@@ -0,0 +1,216 @@
+use crate::aabb::Aabb;
+use crate::hittable::bvh::BvhNode;
+use crate::hittable::triangle;
+use crate::hittable::{HitRecord, Hittable};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use rand::RngCore;
+use std::sync::Arc;
+
+/// Vertex buffers shared by every triangle of a mesh. `normals` and `uvs` are
+/// either empty or hold one entry per position, addressed by the same index.
+struct MeshData<M: Material> {
+    positions: Vec<Point3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<(f64, f64)>,
+    material: M,
+}
+
+/// Indexed triangle mesh. Triangles are kept in their own BVH so a mesh can be
+/// dropped into a scene like any other hittable.
+pub struct TriangleMesh {
+    bvh: BvhNode,
+}
+
+impl TriangleMesh {
+    /// Builds a mesh from vertex buffers and per-face vertex indices.
+    ///
+    /// When `normals` is non-empty, shading normals are interpolated across each
+    /// face; otherwise faces are flat shaded.
+    pub fn new<M: Material + Send + Sync + 'static>(
+        positions: Vec<Point3>,
+        normals: Vec<Vec3>,
+        uvs: Vec<(f64, f64)>,
+        indices: Vec<[usize; 3]>,
+        material: M,
+    ) -> Self {
+        assert!(normals.is_empty() || normals.len() == positions.len());
+        assert!(uvs.is_empty() || uvs.len() == positions.len());
+        assert!(indices.iter().flatten().all(|&i| i < positions.len()));
+
+        let mesh = Arc::new(MeshData {
+            positions,
+            normals,
+            uvs,
+            material,
+        });
+        let triangles = indices
+            .into_iter()
+            .map(|indices| {
+                let triangle: Box<dyn Hittable + Send + Sync> = Box::new(MeshTriangle {
+                    mesh: Arc::clone(&mesh),
+                    indices,
+                });
+                triangle
+            })
+            .collect();
+
+        TriangleMesh {
+            bvh: BvhNode::new(triangles),
+        }
+    }
+}
+
+impl Hittable for TriangleMesh {
+    fn hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rng: &mut dyn RngCore,
+    ) -> Option<HitRecord<'_>> {
+        self.bvh.hit(ray, t_min, t_max, rng)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bvh.bounding_box()
+    }
+}
+
+struct MeshTriangle<M: Material> {
+    mesh: Arc<MeshData<M>>,
+    indices: [usize; 3],
+}
+
+impl<M: Material> MeshTriangle<M> {
+    fn vertices(&self) -> [Point3; 3] {
+        let [i0, i1, i2] = self.indices;
+        let positions = &self.mesh.positions;
+        [positions[i0], positions[i1], positions[i2]]
+    }
+}
+
+impl<M: Material> Hittable for MeshTriangle<M> {
+    fn hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        _rng: &mut dyn RngCore,
+    ) -> Option<HitRecord<'_>> {
+        let vertices = self.vertices();
+        let (t, [b0, b1, b2]) = triangle::intersect(ray, &vertices, t_min, t_max)?;
+        let [i0, i1, i2] = self.indices;
+        let mesh = &self.mesh;
+
+        let uv = if mesh.uvs.is_empty() {
+            (b1, b2)
+        } else {
+            let (uv0, uv1, uv2) = (mesh.uvs[i0], mesh.uvs[i1], mesh.uvs[i2]);
+            (
+                b0 * uv0.0 + b1 * uv1.0 + b2 * uv2.0,
+                b0 * uv0.1 + b1 * uv1.1 + b2 * uv2.1,
+            )
+        };
+
+        let mut record = HitRecord::build_with_face_normal(
+            *ray,
+            triangle::geometric_normal(&vertices),
+            &mesh.material,
+            ray.at(t),
+            t,
+            uv,
+        );
+
+        // Smooth shading: interpolate the vertex normals, but keep them on the
+        // same side of the surface as the geometric normal chosen above.
+        if !mesh.normals.is_empty() {
+            let (n0, n1, n2) = (mesh.normals[i0], mesh.normals[i1], mesh.normals[i2]);
+            let shading_normal = (b0 * n0 + b1 * n1 + b2 * n2).unit_vector();
+            record.normal = if record.front_face {
+                shading_normal
+            } else {
+                -shading_normal
+            };
+        }
+
+        Some(record)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(triangle::bounding_box(&self.vertices()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::lambertian::Lambertian;
+    use crate::vec3::Color;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn quad(normals: Vec<Vec3>) -> TriangleMesh {
+        TriangleMesh::new(
+            vec![
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(1.0, 0.0, 0.0),
+                Point3::new(1.0, 1.0, 0.0),
+                Point3::new(0.0, 1.0, 0.0),
+            ],
+            normals,
+            vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)],
+            vec![[0, 1, 2], [0, 2, 3]],
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        )
+    }
+
+    #[test]
+    fn test_flat_shading() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mesh = quad(Vec::new());
+        let ray = Ray::new(Point3::new(0.2, 0.7, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let record = mesh.hit(&ray, 0.001, f64::INFINITY, &mut rng).unwrap();
+        assert_eq!(Vec3::new(0.0, 0.0, 1.0), record.normal);
+        assert!(approx_eq!(f64, record.u, 0.2, epsilon = 1e-12));
+        assert!(approx_eq!(f64, record.v, 0.7, epsilon = 1e-12));
+    }
+
+    #[test]
+    fn test_smooth_shading_interpolates_normals() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let tilted = Vec3::new(1.0, 0.0, 1.0).unit_vector();
+        let up = Vec3::new(0.0, 0.0, 1.0);
+        let mesh = quad(vec![up, tilted, tilted, up]);
+
+        let ray = Ray::new(Point3::new(0.5, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let record = mesh.hit(&ray, 0.001, f64::INFINITY, &mut rng).unwrap();
+        let expected = (0.5 * up + 0.5 * tilted).unit_vector();
+        assert!(approx_eq!(
+            f64,
+            record.normal.dot(expected),
+            1.0,
+            epsilon = 1e-12
+        ));
+
+        let from_below = Ray::new(Point3::new(0.5, 0.5, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        let record = mesh
+            .hit(&from_below, 0.001, f64::INFINITY, &mut rng)
+            .unwrap();
+        assert!(!record.front_face);
+        assert!(approx_eq!(
+            f64,
+            record.normal.dot(-expected),
+            1.0,
+            epsilon = 1e-12
+        ));
+    }
+
+    #[test]
+    fn test_bounding_box_covers_all_triangles() {
+        let bbox = quad(Vec::new()).bounding_box().unwrap();
+        assert!(bbox.minimum.x <= 0.0 && bbox.minimum.y <= 0.0);
+        assert!(bbox.maximum.x >= 1.0 && bbox.maximum.y >= 1.0);
+    }
+}
@@ -0,0 +1,142 @@
+use crate::aabb::Aabb;
+use crate::hittable::sphere::hit_sphere;
+use crate::hittable::{HitRecord, Hittable};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use rand::RngCore;
+
+/// A sphere moving in a straight line from `center0` at `time0` to `center1`
+/// at `time1`. Seen through a camera with an open shutter, it is motion blurred.
+pub struct MovingSphere<M: Material> {
+    pub center0: Point3,
+    pub center1: Point3,
+    pub time0: f64,
+    pub time1: f64,
+    pub radius: f64,
+    pub material: M,
+}
+
+impl<M: Material> MovingSphere<M> {
+    pub fn new(
+        center0: Point3,
+        center1: Point3,
+        time0: f64,
+        time1: f64,
+        radius: f64,
+        material: M,
+    ) -> Self {
+        MovingSphere {
+            center0,
+            center1,
+            time0,
+            time1,
+            radius,
+            material,
+        }
+    }
+
+    /// The center at `time`. Outside `[time0, time1]` the sphere stays put at
+    /// the nearer end of its path, which keeps it inside its bounding box.
+    pub fn center(&self, time: f64) -> Point3 {
+        if self.time0 == self.time1 {
+            return self.center0;
+        }
+        let fraction = (time - self.time0) / (self.time1 - self.time0);
+        self.center0 + fraction.clamp(0.0, 1.0) * (self.center1 - self.center0)
+    }
+}
+
+impl<M: Material> Hittable for MovingSphere<M> {
+    fn hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        _rng: &mut dyn RngCore,
+    ) -> Option<HitRecord<'_>> {
+        hit_sphere(
+            self.center(ray.time),
+            self.radius,
+            &self.material,
+            ray,
+            t_min,
+            t_max,
+        )
+    }
+
+    /// Covers the sphere's whole path from `time0` to `time1`.
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = self.radius.abs();
+        let radius = Vec3::new(r, r, r);
+        let box0 = Aabb::new(self.center0 - radius, self.center0 + radius);
+        let box1 = Aabb::new(self.center1 - radius, self.center1 + radius);
+        Some(Aabb::surrounding_box(box0, box1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable::bvh::BvhNode;
+    use crate::material::lambertian::Lambertian;
+    use crate::vec3::Color;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn sphere() -> MovingSphere<Lambertian> {
+        MovingSphere::new(
+            Point3::new(0.0, 0.0, -2.0),
+            Point3::new(0.0, 1.0, -2.0),
+            0.0,
+            1.0,
+            0.5,
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        )
+    }
+
+    #[test]
+    fn test_center_moves_over_time() {
+        let sphere = sphere();
+        assert_eq!(Point3::new(0.0, 0.0, -2.0), sphere.center(0.0));
+        assert_eq!(Point3::new(0.0, 0.5, -2.0), sphere.center(0.5));
+        assert_eq!(Point3::new(0.0, 1.0, -2.0), sphere.center(1.0));
+        assert_eq!(Point3::new(0.0, 0.0, -2.0), sphere.center(-1.0));
+        assert_eq!(Point3::new(0.0, 1.0, -2.0), sphere.center(3.0));
+    }
+
+    #[test]
+    fn test_hit_outside_interval_through_bvh() {
+        let mut rng = StdRng::seed_from_u64(0);
+        // A shutter open longer than the sphere moves still finds it at the
+        // ends of its path, inside the box the BVH tests first.
+        let bvh = BvhNode::new(vec![Box::new(sphere())]);
+        let direction = Vec3::new(0.0, 0.0, -1.0);
+        let before = Ray::with_time(Point3::new(0.0, 0.0, 0.0), direction, -1.0);
+        let after = Ray::with_time(Point3::new(0.0, 1.0, 0.0), direction, 3.0);
+        assert!(bvh.hit(&before, 0.001, f64::INFINITY, &mut rng).is_some());
+        let record = bvh.hit(&after, 0.001, f64::INFINITY, &mut rng).unwrap();
+        assert!(approx_eq!(f64, record.t, 1.5, ulps = 2));
+    }
+
+    #[test]
+    fn test_hit_depends_on_ray_time() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let sphere = sphere();
+        let origin = Point3::new(0.0, 1.0, 0.0);
+        let direction = Vec3::new(0.0, 0.0, -1.0);
+
+        let early = Ray::with_time(origin, direction, 0.0);
+        let late = Ray::with_time(origin, direction, 1.0);
+        assert!(sphere.hit(&early, 0.001, f64::INFINITY, &mut rng).is_none());
+        let record = sphere.hit(&late, 0.001, f64::INFINITY, &mut rng).unwrap();
+        assert!(approx_eq!(f64, record.t, 1.5, ulps = 2));
+    }
+
+    #[test]
+    fn test_bounding_box_covers_path() {
+        let bbox = sphere().bounding_box().unwrap();
+        assert_eq!(Point3::new(-0.5, -0.5, -2.5), bbox.minimum);
+        assert_eq!(Point3::new(0.5, 1.5, -1.5), bbox.maximum);
+    }
+}
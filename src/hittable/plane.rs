@@ -0,0 +1,99 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::material::Material;
+use crate::onb::Onb;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use rand::RngCore;
+
+/// An infinite plane through `point`. It has no bounding box, so keep it out of
+/// a `BvhNode` and test it alongside the hierarchy instead.
+///
+/// Texture coordinates are distances in world units along two fixed tangents,
+/// so they are unbounded; use a repeating or solid texture.
+pub struct Plane<M: Material> {
+    point: Point3,
+    frame: Onb,
+    material: M,
+}
+
+impl<M: Material> Plane<M> {
+    pub fn new(point: Point3, normal: Vec3, material: M) -> Self {
+        assert!(
+            normal.length_squared() > 0.0,
+            "Plane normal must be non-zero"
+        );
+        Plane {
+            point,
+            frame: Onb::new(normal),
+            material,
+        }
+    }
+
+    pub fn normal(&self) -> Vec3 {
+        self.frame.w
+    }
+}
+
+impl<M: Material> Hittable for Plane<M> {
+    fn hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        _rng: &mut dyn RngCore,
+    ) -> Option<HitRecord<'_>> {
+        let normal = self.frame.w;
+        let denom = normal.dot(ray.direction);
+        if denom.abs() < 1e-12 {
+            return None;
+        }
+
+        let t = normal.dot(self.point - ray.origin) / denom;
+        if t < t_min || t_max < t {
+            return None;
+        }
+
+        let point = ray.at(t);
+        let offset = point - self.point;
+        Some(HitRecord::build_with_face_normal(
+            *ray,
+            normal,
+            &self.material,
+            point,
+            t,
+            (offset.dot(self.frame.u), offset.dot(self.frame.v)),
+        ))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::lambertian::Lambertian;
+    use crate::vec3::Color;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_hit() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let plane = Plane::new(
+            Point3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        );
+        let ray = Ray::new(Point3::new(100.0, 1.0, -50.0), Vec3::new(0.0, -1.0, 0.0));
+        let record = plane.hit(&ray, 0.001, f64::INFINITY, &mut rng).unwrap();
+        assert!(approx_eq!(f64, record.t, 2.0, ulps = 2));
+        assert_eq!(Vec3::new(0.0, 1.0, 0.0), record.normal);
+
+        let away = Ray::new(Point3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        assert!(plane.hit(&away, 0.001, f64::INFINITY, &mut rng).is_none());
+        assert!(plane.bounding_box().is_none());
+    }
+}
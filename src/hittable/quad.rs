@@ -0,0 +1,183 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use rand::{Rng, RngCore};
+
+/// Thickness given to the bounding box of an axis-aligned quad.
+const BOX_PADDING: f64 = 1e-4;
+
+/// A parallelogram with corner `q` and edges `u` and `v`. The front face is the
+/// one `u × v` points out of, and `(u, v)` texture coordinates run along the
+/// edges from 0 to 1.
+pub struct Quad<M: Material> {
+    q: Point3,
+    u: Vec3,
+    v: Vec3,
+    material: M,
+    normal: Vec3,
+    /// Plane offset: points `p` on the quad satisfy `normal · p = d`.
+    d: f64,
+    /// `n / (n · n)` for the unnormalized normal `n`, which turns a point in the
+    /// plane into its edge coordinates.
+    w: Vec3,
+}
+
+impl<M: Material> Quad<M> {
+    pub fn new(q: Point3, u: Vec3, v: Vec3, material: M) -> Self {
+        let n = u.cross(v);
+        assert!(n.length_squared() > 0.0, "Quad edges must not be parallel");
+        let normal = n.unit_vector();
+        Quad {
+            q,
+            u,
+            v,
+            material,
+            normal,
+            d: normal.dot(q),
+            w: n / n.length_squared(),
+        }
+    }
+
+    pub fn corner(&self) -> Point3 {
+        self.q
+    }
+
+    pub fn edges(&self) -> (Vec3, Vec3) {
+        (self.u, self.v)
+    }
+
+    pub fn normal(&self) -> Vec3 {
+        self.normal
+    }
+
+    /// The hit with the quad itself, which `pdf_value` needs as well.
+    fn intersect(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let denom = self.normal.dot(ray.direction);
+        if denom.abs() < 1e-12 {
+            return None;
+        }
+
+        let t = (self.d - self.normal.dot(ray.origin)) / denom;
+        if t < t_min || t_max < t {
+            return None;
+        }
+
+        let point = ray.at(t);
+        let planar = point - self.q;
+        let alpha = self.w.dot(planar.cross(self.v));
+        let beta = self.w.dot(self.u.cross(planar));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+
+        Some(HitRecord::build_with_face_normal(
+            *ray,
+            self.normal,
+            &self.material,
+            point,
+            t,
+            (alpha, beta),
+        ))
+    }
+}
+
+impl<M: Material> Hittable for Quad<M> {
+    fn hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        _rng: &mut dyn RngCore,
+    ) -> Option<HitRecord<'_>> {
+        self.intersect(ray, t_min, t_max)
+    }
+
+    /// Samples points uniformly over the quad's area, converted to a density
+    /// over solid angle.
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let record = match self.intersect(&Ray::new(origin, direction), 0.001, f64::INFINITY) {
+            Some(record) => record,
+            None => return 0.0,
+        };
+        let distance_squared = record.t * record.t * direction.length_squared();
+        let cosine = (direction.dot(self.normal) / direction.length()).abs();
+        let area = self.u.cross(self.v).length();
+        distance_squared / (cosine * area)
+    }
+
+    fn random(&self, origin: Point3, rng: &mut dyn RngCore) -> Vec3 {
+        let point = self.q + rng.gen::<f64>() * self.u + rng.gen::<f64>() * self.v;
+        point - origin
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let diagonal = Aabb::surrounding_box(
+            Aabb::new(self.q, self.q),
+            Aabb::new(self.q + self.u + self.v, self.q + self.u + self.v),
+        );
+        let other = self.q + self.u;
+        let corners = Aabb::surrounding_box(diagonal, Aabb::new(other, other));
+        let other = self.q + self.v;
+        Some(Aabb::surrounding_box(corners, Aabb::new(other, other)).pad(BOX_PADDING))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::lambertian::Lambertian;
+    use crate::vec3::Color;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn quad() -> Quad<Lambertian> {
+        Quad::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        )
+    }
+
+    #[test]
+    fn test_hit_sets_normal_and_uv() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let quad = quad();
+        let ray = Ray::new(Point3::new(1.5, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let record = quad.hit(&ray, 0.001, f64::INFINITY, &mut rng).unwrap();
+        assert!(approx_eq!(f64, record.t, 1.0, ulps = 2));
+        assert_eq!(Vec3::new(0.0, 0.0, 1.0), record.normal);
+        assert!(record.front_face);
+        assert!(approx_eq!(f64, record.u, 0.5, epsilon = 1e-12));
+        assert!(approx_eq!(f64, record.v, 0.5, epsilon = 1e-12));
+
+        let from_behind = Ray::new(Point3::new(1.5, 0.5, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        let record = quad
+            .hit(&from_behind, 0.001, f64::INFINITY, &mut rng)
+            .unwrap();
+        assert!(!record.front_face);
+        assert_eq!(Vec3::new(0.0, 0.0, -1.0), record.normal);
+    }
+
+    #[test]
+    fn test_misses_outside_edges() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let quad = quad();
+        // Inside the bounding rectangle but outside the parallelogram.
+        let ray = Ray::new(Point3::new(0.2, 0.8, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(quad.hit(&ray, 0.001, f64::INFINITY, &mut rng).is_none());
+        let parallel = Ray::new(Point3::new(0.0, 0.5, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(quad
+            .hit(&parallel, 0.001, f64::INFINITY, &mut rng)
+            .is_none());
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let bbox = quad().bounding_box().unwrap();
+        assert_eq!(0.0, bbox.minimum.x);
+        assert_eq!(Point3::new(3.0, 1.0, 0.5e-4), bbox.maximum);
+    }
+}
@@ -1,7 +1,11 @@
+use crate::aabb::Aabb;
 use crate::hittable::{HitRecord, Hittable};
 use crate::material::Material;
+use crate::onb::Onb;
 use crate::ray::Ray;
-use crate::vec3::Point3;
+use crate::vec3::{Point3, Vec3};
+use rand::{Rng, RngCore};
+use std::f64::consts::PI;
 
 pub struct Sphere<M: Material> {
     pub center: Point3,
@@ -17,39 +21,182 @@ impl<M: Material> Sphere<M> {
             material,
         }
     }
-}
 
-impl<M: Material> Hittable for Sphere<M> {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        let oc = ray.origin - self.center;
-        let a = ray.direction.length_squared();
-        let half_b = oc.dot(ray.direction);
-        let c = oc.length_squared() - self.radius.powi(2);
-        let discriminant = half_b.powi(2) - a * c;
-        if discriminant < 0.0 {
-            return None;
+    /// Solid angle the sphere covers as seen from `origin`: the whole sphere of
+    /// directions from inside it.
+    fn solid_angle(&self, origin: Point3) -> f64 {
+        let distance_squared = (self.center - origin).length_squared();
+        let radius_squared = self.radius * self.radius;
+        if distance_squared <= radius_squared {
+            return 4.0 * PI;
         }
+        let cos_theta_max = (1.0 - radius_squared / distance_squared).sqrt();
+        2.0 * PI * (1.0 - cos_theta_max)
+    }
+}
+
+/// Maps a point on the unit sphere to `(u, v)` in `[0, 1]`, with `u` running
+/// around the Y axis starting from -X and `v` running from -Y up to +Y.
+fn get_sphere_uv(p: Point3) -> (f64, f64) {
+    let theta = (-p.y).acos();
+    let phi = (-p.z).atan2(p.x) + PI;
+    (phi / (2.0 * PI), theta / PI)
+}
+
+/// Intersects `ray` with a sphere. Shared by `Sphere` and `MovingSphere`, which
+/// differ only in where the center is.
+pub(crate) fn hit_sphere<'a>(
+    center: Point3,
+    radius: f64,
+    material: &'a dyn Material,
+    ray: &Ray,
+    t_min: f64,
+    t_max: f64,
+) -> Option<HitRecord<'a>> {
+    let oc = ray.origin - center;
+    let a = ray.direction.length_squared();
+    let half_b = oc.dot(ray.direction);
+    let c = oc.length_squared() - radius.powi(2);
+    let discriminant = half_b.powi(2) - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
 
-        // Find the nearest root that lies within the acceptable range
-        let sqrt_discriminant = discriminant.sqrt();
-        let mut root = (-half_b - sqrt_discriminant) / a;
+    // Find the nearest root that lies within the acceptable range
+    let sqrt_discriminant = discriminant.sqrt();
+    let mut root = (-half_b - sqrt_discriminant) / a;
+    if root < t_min || t_max < root {
+        root = (-half_b + sqrt_discriminant) / a;
         if root < t_min || t_max < root {
-            root = (-half_b + sqrt_discriminant) / a;
-            if root < t_min || t_max < root {
-                return None;
-            }
+            return None;
         }
+    }
+
+    let t = root;
+    let point = ray.at(t);
+    let outward_normal = (point - center) / radius;
+    // A hollow sphere's negative radius flips its normal inwards, but the
+    // texture should still be laid out as on a solid sphere.
+    let uv = get_sphere_uv((point - center) / radius.abs());
 
-        let t = root;
-        let point = ray.at(t);
-        let outward_normal = (point - self.center) / self.radius;
+    Some(HitRecord::build_with_face_normal(
+        *ray,
+        outward_normal,
+        material,
+        point,
+        t,
+        uv,
+    ))
+}
+
+impl<M: Material> Hittable for Sphere<M> {
+    fn hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        _rng: &mut dyn RngCore,
+    ) -> Option<HitRecord<'_>> {
+        hit_sphere(self.center, self.radius, &self.material, ray, t_min, t_max)
+    }
 
-        Some(HitRecord::build_with_face_normal(
-            *ray,
-            outward_normal,
+    /// Samples the cone of directions the sphere subtends from `origin`.
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let ray = Ray::new(origin, direction);
+        if hit_sphere(
+            self.center,
+            self.radius,
             &self.material,
-            point,
-            t,
-        ))
+            &ray,
+            0.001,
+            f64::INFINITY,
+        )
+        .is_none()
+        {
+            return 0.0;
+        }
+        1.0 / self.solid_angle(origin)
+    }
+
+    fn random(&self, origin: Point3, rng: &mut dyn RngCore) -> Vec3 {
+        let to_center = self.center - origin;
+        let distance_squared = to_center.length_squared();
+        let radius_squared = self.radius * self.radius;
+        if distance_squared <= radius_squared {
+            return Vec3::random_unit_vector(rng);
+        }
+
+        let cos_theta_max = (1.0 - radius_squared / distance_squared).sqrt();
+        let r1: f64 = rng.gen();
+        let r2: f64 = rng.gen();
+        let z = 1.0 + r2 * (cos_theta_max - 1.0);
+        let phi = 2.0 * PI * r1;
+        let sin_theta = (1.0 - z * z).sqrt();
+        Onb::new(to_center).local(Vec3::new(phi.cos() * sin_theta, phi.sin() * sin_theta, z))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // Hollow spheres use a negative radius, so take the magnitude here.
+        let r = self.radius.abs();
+        let radius = Vec3::new(r, r, r);
+        Some(Aabb::new(self.center - radius, self.center + radius))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::lambertian::Lambertian;
+    use crate::vec3::Color;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_sphere_uv() {
+        let uv = get_sphere_uv;
+        let check = |p: Point3, (u, v): (f64, f64)| {
+            let (act_u, act_v) = uv(p);
+            assert!(approx_eq!(f64, u, act_u, epsilon = 1e-12), "u at {:?}", p);
+            assert!(approx_eq!(f64, v, act_v, epsilon = 1e-12), "v at {:?}", p);
+        };
+        check(Point3::new(1.0, 0.0, 0.0), (0.5, 0.5));
+        check(Point3::new(-1.0, 0.0, 0.0), (0.0, 0.5));
+        check(Point3::new(0.0, 1.0, 0.0), (0.5, 1.0));
+        check(Point3::new(0.0, -1.0, 0.0), (0.5, 0.0));
+        check(Point3::new(0.0, 0.0, 1.0), (0.25, 0.5));
+        check(Point3::new(0.0, 0.0, -1.0), (0.75, 0.5));
+    }
+
+    #[test]
+    fn test_hit_sets_uv() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let sphere = Sphere::new(
+            Point3::new(0.0, 0.0, -2.0),
+            1.0,
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        );
+        let ray = Ray::new(Point3::default(), Vec3::new(0.0, 0.0, -1.0));
+        let record = sphere.hit(&ray, 0.001, f64::INFINITY, &mut rng).unwrap();
+        assert!(approx_eq!(f64, record.u, 0.25, epsilon = 1e-12));
+        assert!(approx_eq!(f64, record.v, 0.5, epsilon = 1e-12));
+    }
+
+    #[test]
+    fn test_hollow_sphere_uv() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut uv = |radius: f64| {
+            let sphere = Sphere::new(
+                Point3::new(0.0, 0.0, -2.0),
+                radius,
+                Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+            );
+            let ray = Ray::new(Point3::new(0.3, 0.2, 0.0), Vec3::new(0.0, 0.0, -1.0));
+            let record = sphere.hit(&ray, 0.001, f64::INFINITY, &mut rng).unwrap();
+            (record.u, record.v)
+        };
+        let (u, v) = uv(1.0);
+        let (hollow_u, hollow_v) = uv(-1.0);
+        assert!(approx_eq!(f64, u, hollow_u, epsilon = 1e-12));
+        assert!(approx_eq!(f64, v, hollow_v, epsilon = 1e-12));
     }
 }
@@ -0,0 +1,89 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use rand::RngCore;
+
+/// Gives an object a stable ID, which its hits report as `object_id` for the
+/// object ID AOV.
+pub struct TaggedHittable<H: Hittable> {
+    object: H,
+    id: u32,
+}
+
+impl<H: Hittable> TaggedHittable<H> {
+    pub fn new(object: H, id: u32) -> Self {
+        TaggedHittable { object, id }
+    }
+}
+
+/// Tags each object with its position in `objects`, counting from 1.
+pub fn tag_objects(
+    objects: Vec<Box<dyn Hittable + Send + Sync>>,
+) -> Vec<Box<dyn Hittable + Send + Sync>> {
+    objects
+        .into_iter()
+        .enumerate()
+        .map(|(i, object)| -> Box<dyn Hittable + Send + Sync> {
+            Box::new(TaggedHittable::new(object, i as u32 + 1))
+        })
+        .collect()
+}
+
+impl<H: Hittable> Hittable for TaggedHittable<H> {
+    fn hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rng: &mut dyn RngCore,
+    ) -> Option<HitRecord<'_>> {
+        let mut record = self.object.hit(ray, t_min, t_max, rng)?;
+        record.object_id = self.id;
+        Some(record)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.object.bounding_box()
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        self.object.pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: Point3, rng: &mut dyn RngCore) -> Vec3 {
+        self.object.random(origin, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable::sphere::Sphere;
+    use crate::material::lambertian::Lambertian;
+    use crate::vec3::Color;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_tag_objects() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let sphere = |x: f64| -> Box<dyn Hittable + Send + Sync> {
+            Box::new(Sphere::new(
+                Point3::new(x, 0.0, 0.0),
+                0.5,
+                Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+            ))
+        };
+        let world = tag_objects(vec![sphere(0.0), sphere(2.0)]);
+        let mut id = |x: f64| {
+            let ray = Ray::new(Point3::new(x, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+            world
+                .hit(&ray, 0.001, f64::INFINITY, &mut rng)
+                .map(|record| record.object_id)
+        };
+        assert_eq!(Some(1), id(0.0));
+        assert_eq!(Some(2), id(2.0));
+        assert_eq!(None, id(1.0));
+    }
+}
@@ -0,0 +1,221 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use rand::RngCore;
+
+/// Thickness given to the bounding box of an axis-aligned triangle.
+const BOX_PADDING: f64 = 1e-4;
+
+pub struct Triangle<M: Material> {
+    pub vertices: [Point3; 3],
+    pub material: M,
+}
+
+impl<M: Material> Triangle<M> {
+    pub fn new(v0: Point3, v1: Point3, v2: Point3, material: M) -> Self {
+        Triangle {
+            vertices: [v0, v1, v2],
+            material,
+        }
+    }
+}
+
+impl<M: Material> Hittable for Triangle<M> {
+    fn hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        _rng: &mut dyn RngCore,
+    ) -> Option<HitRecord<'_>> {
+        let (t, [_, b1, b2]) = intersect(ray, &self.vertices, t_min, t_max)?;
+        Some(HitRecord::build_with_face_normal(
+            *ray,
+            geometric_normal(&self.vertices),
+            &self.material,
+            ray.at(t),
+            t,
+            (b1, b2),
+        ))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(bounding_box(&self.vertices))
+    }
+}
+
+/// Unit normal of the triangle, wound counter-clockwise.
+pub fn geometric_normal(vertices: &[Point3; 3]) -> Vec3 {
+    let [v0, v1, v2] = *vertices;
+    (v1 - v0).cross(v2 - v0).unit_vector()
+}
+
+pub fn bounding_box(vertices: &[Point3; 3]) -> Aabb {
+    let [v0, v1, v2] = *vertices;
+    let corners = Aabb::surrounding_box(Aabb::new(v0, v0), Aabb::new(v1, v1));
+    Aabb::surrounding_box(corners, Aabb::new(v2, v2)).pad(BOX_PADDING)
+}
+
+/// Watertight ray/triangle intersection (Woop, Benthin and Wald, 2013).
+///
+/// Returns the ray parameter and the barycentric weights of the three
+/// vertices. Rays through a shared edge or vertex always hit at least one of
+/// the adjoining triangles, so meshes render without cracks.
+pub fn intersect(
+    ray: &Ray,
+    vertices: &[Point3; 3],
+    t_min: f64,
+    t_max: f64,
+) -> Option<(f64, [f64; 3])> {
+    let dir = ray.direction;
+
+    // Permute axes so the ray travels mostly along z.
+    let kz = max_dimension(Vec3::new(dir.x.abs(), dir.y.abs(), dir.z.abs()));
+    let mut kx = (kz + 1) % 3;
+    let mut ky = (kx + 1) % 3;
+    if dir[kz] < 0.0 {
+        std::mem::swap(&mut kx, &mut ky);
+    }
+
+    // Shear so the ray direction becomes +z.
+    let sx = dir[kx] / dir[kz];
+    let sy = dir[ky] / dir[kz];
+    let sz = 1.0 / dir[kz];
+
+    let a = vertices[0] - ray.origin;
+    let b = vertices[1] - ray.origin;
+    let c = vertices[2] - ray.origin;
+
+    let (ax, ay) = (a[kx] - sx * a[kz], a[ky] - sy * a[kz]);
+    let (bx, by) = (b[kx] - sx * b[kz], b[ky] - sy * b[kz]);
+    let (cx, cy) = (c[kx] - sx * c[kz], c[ky] - sy * c[kz]);
+
+    // Scaled barycentric coordinates as 2D edge functions.
+    let u = cx * by - cy * bx;
+    let v = ax * cy - ay * cx;
+    let w = bx * ay - by * ax;
+
+    if (u < 0.0 || v < 0.0 || w < 0.0) && (u > 0.0 || v > 0.0 || w > 0.0) {
+        return None;
+    }
+
+    let det = u + v + w;
+    if det == 0.0 {
+        return None;
+    }
+
+    let scaled_t = u * sz * a[kz] + v * sz * b[kz] + w * sz * c[kz];
+    let t = scaled_t / det;
+    if t < t_min || t_max < t {
+        return None;
+    }
+
+    Some((t, [u / det, v / det, w / det]))
+}
+
+fn max_dimension(v: Vec3) -> usize {
+    if v.x > v.y {
+        if v.x > v.z {
+            0
+        } else {
+            2
+        }
+    } else if v.y > v.z {
+        1
+    } else {
+        2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::lambertian::Lambertian;
+    use crate::vec3::Color;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn unit_triangle() -> Triangle<Lambertian> {
+        Triangle::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        )
+    }
+
+    #[test]
+    fn test_hit_inside() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let ray = Ray::new(Point3::new(0.25, 0.25, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let triangle = unit_triangle();
+        let record = triangle.hit(&ray, 0.001, f64::INFINITY, &mut rng).unwrap();
+        assert!(approx_eq!(f64, record.t, 1.0, ulps = 2));
+        assert!(approx_eq!(f64, record.u, 0.25, ulps = 2));
+        assert!(approx_eq!(f64, record.v, 0.25, ulps = 2));
+        assert!(record.front_face);
+        assert_eq!(Vec3::new(0.0, 0.0, 1.0), record.normal);
+    }
+
+    #[test]
+    fn test_hit_back_face() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let ray = Ray::new(Point3::new(0.25, 0.25, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        let triangle = unit_triangle();
+        let record = triangle.hit(&ray, 0.001, f64::INFINITY, &mut rng).unwrap();
+        assert!(!record.front_face);
+        assert_eq!(Vec3::new(0.0, 0.0, -1.0), record.normal);
+    }
+
+    #[test]
+    fn test_miss() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let triangle = unit_triangle();
+        let outside = Ray::new(Point3::new(0.75, 0.75, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let parallel = Ray::new(Point3::new(0.25, 0.25, 1.0), Vec3::new(1.0, 0.0, 0.0));
+        let too_far = Ray::new(Point3::new(0.25, 0.25, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(triangle
+            .hit(&outside, 0.001, f64::INFINITY, &mut rng)
+            .is_none());
+        assert!(triangle
+            .hit(&parallel, 0.001, f64::INFINITY, &mut rng)
+            .is_none());
+        assert!(triangle.hit(&too_far, 0.001, 0.5, &mut rng).is_none());
+    }
+
+    #[test]
+    fn test_shared_edge_is_watertight() {
+        // Two triangles forming the unit square, split along the diagonal.
+        let lower = [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+        ];
+        let upper = [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ];
+        for i in 0..=100 {
+            let s = i as f64 / 100.0;
+            let ray = Ray::new(Point3::new(s, s, 1.0), Vec3::new(0.0, 0.0, -1.0));
+            let hit_lower = intersect(&ray, &lower, 0.001, f64::INFINITY).is_some();
+            let hit_upper = intersect(&ray, &upper, 0.001, f64::INFINITY).is_some();
+            assert!(
+                hit_lower || hit_upper,
+                "ray through diagonal at {} leaked",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn test_axis_aligned_bounding_box_has_thickness() {
+        let bbox = unit_triangle().bounding_box().unwrap();
+        assert!(bbox.maximum.z > bbox.minimum.z);
+        let ray = Ray::new(Point3::new(0.25, 0.25, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(bbox.hit(&ray, 0.001, f64::INFINITY));
+    }
+}
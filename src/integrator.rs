@@ -0,0 +1,66 @@
+pub mod ambient_occlusion;
+pub mod debug;
+pub mod path;
+pub mod whitted;
+
+use crate::hittable::Hittable;
+use crate::integrator::ambient_occlusion::AmbientOcclusion;
+use crate::integrator::debug::{Depth, MaterialIds, Normals};
+use crate::integrator::path::PathTracer;
+use crate::integrator::whitted::Whitted;
+use crate::ray::Ray;
+use crate::render::Background;
+use crate::vec3::Color;
+use rand::RngCore;
+
+/// Turns camera rays into colors. The renderer averages many calls per pixel,
+/// so an integrator may return a noisy estimate as long as its mean is right.
+/// All of its randomness should come from `rng`, which keeps renders
+/// reproducible.
+pub trait Integrator {
+    fn ray_color(
+        &self,
+        ray: &Ray,
+        world: &dyn Hittable,
+        lights: &[Box<dyn Hittable + Send + Sync>],
+        background: Background,
+        rng: &mut dyn RngCore,
+    ) -> Color;
+}
+
+/// Which integrator to render with, as chosen in a scene file or on the
+/// command line.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum IntegratorKind {
+    /// Unbiased path tracing with light sampling: the default.
+    #[default]
+    Path,
+    /// Fraction of the hemisphere above each surface left open within
+    /// `distance`.
+    AmbientOcclusion { distance: f64 },
+    /// Mirror and glass bounces with direct lighting only.
+    Whitted,
+    /// Surface normals mapped to colors.
+    Normals,
+    /// Distance to the first hit, brighter when closer.
+    Depth,
+    /// A distinct flat color for each material.
+    MaterialIds,
+}
+
+impl IntegratorKind {
+    /// Builds the integrator, capping paths at `depth` bounces where that
+    /// applies.
+    pub fn build(self, depth: i32) -> Box<dyn Integrator + Send + Sync> {
+        match self {
+            IntegratorKind::Path => Box::new(PathTracer::new(depth)),
+            IntegratorKind::AmbientOcclusion { distance } => {
+                Box::new(AmbientOcclusion::new(distance))
+            }
+            IntegratorKind::Whitted => Box::new(Whitted::new(depth)),
+            IntegratorKind::Normals => Box::new(Normals),
+            IntegratorKind::Depth => Box::new(Depth),
+            IntegratorKind::MaterialIds => Box::new(MaterialIds),
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use crate::hittable::Hittable;
+use crate::integrator::Integrator;
+use crate::pdf::{CosinePdf, Pdf};
+use crate::ray::Ray;
+use crate::render::Background;
+use crate::vec3::Color;
+use rand::RngCore;
+
+/// Ambient occlusion: white where the hemisphere above the first surface hit is
+/// open and black where nearby geometry covers it, weighted by cosine. Misses
+/// show the background.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AmbientOcclusion {
+    /// How far away geometry still counts as covering a surface.
+    pub distance: f64,
+}
+
+impl AmbientOcclusion {
+    pub fn new(distance: f64) -> Self {
+        AmbientOcclusion { distance }
+    }
+}
+
+impl Integrator for AmbientOcclusion {
+    fn ray_color(
+        &self,
+        ray: &Ray,
+        world: &dyn Hittable,
+        _lights: &[Box<dyn Hittable + Send + Sync>],
+        background: Background,
+        rng: &mut dyn RngCore,
+    ) -> Color {
+        let record = match world.hit(ray, 0.001, f64::INFINITY, rng) {
+            Some(record) => record,
+            None => return background.color(ray),
+        };
+
+        // Cosine-distributed unit directions, so one visibility test per sample
+        // averages to the cosine-weighted open fraction.
+        let direction = CosinePdf::new(record.normal).generate(rng);
+        let probe = Ray::with_time(record.p, direction, ray.time);
+        match world.hit(&probe, 0.001, self.distance, rng) {
+            Some(_) => Color::default(),
+            None => Color::new(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable::quad::Quad;
+    use crate::hittable::sphere::Sphere;
+    use crate::material::lambertian::Lambertian;
+    use crate::vec3::{Point3, Vec3};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_occlusion() {
+        let grey = || Lambertian::new(Color::new(0.5, 0.5, 0.5));
+        let ray = Ray::new(Point3::default(), Vec3::new(0.0, -1.0, 0.0));
+        let sky = Background::Sky;
+        let mut rng = StdRng::seed_from_u64(0);
+
+        // Inside a unit sphere every probe hits the far wall within 2.
+        let inside: Vec<Box<dyn Hittable + Send + Sync>> =
+            vec![Box::new(Sphere::new(Point3::default(), 1.0, grey()))];
+        let ao = AmbientOcclusion::new(2.5);
+        for _ in 0..100 {
+            assert_eq!(
+                Color::default(),
+                ao.ray_color(&ray, &inside, &[], sky, &mut rng)
+            );
+        }
+
+        // Nothing covers a lone floor.
+        let floor: Vec<Box<dyn Hittable + Send + Sync>> = vec![Box::new(Quad::new(
+            Point3::new(-5.0, -1.0, -5.0),
+            Vec3::new(0.0, 0.0, 10.0),
+            Vec3::new(10.0, 0.0, 0.0),
+            grey(),
+        ))];
+        for _ in 0..100 {
+            assert_eq!(
+                Color::new(1.0, 1.0, 1.0),
+                ao.ray_color(&ray, &floor, &[], sky, &mut rng)
+            );
+        }
+    }
+}
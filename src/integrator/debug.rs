@@ -0,0 +1,130 @@
+//! Integrators that show one property of the first surface each ray hits,
+//! for checking geometry and scene files rather than making pictures. Rays
+//! that miss are black.
+
+use crate::aov::id_color;
+use crate::hittable::Hittable;
+use crate::integrator::Integrator;
+use crate::ray::Ray;
+use crate::render::Background;
+use crate::vec3::{Color, Vec3};
+use rand::RngCore;
+
+/// Outward surface normals, mapped from `[-1, 1]` to `[0, 1]` per axis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Normals;
+
+impl Integrator for Normals {
+    fn ray_color(
+        &self,
+        ray: &Ray,
+        world: &dyn Hittable,
+        _lights: &[Box<dyn Hittable + Send + Sync>],
+        _background: Background,
+        rng: &mut dyn RngCore,
+    ) -> Color {
+        match world.hit(ray, 0.001, f64::INFINITY, rng) {
+            Some(record) => {
+                let outward = if record.front_face {
+                    record.normal
+                } else {
+                    -record.normal
+                };
+                0.5 * (outward + Vec3::new(1.0, 1.0, 1.0))
+            }
+            None => Color::default(),
+        }
+    }
+}
+
+/// Distance `d` from the ray origin to the first hit, shown as `1 / (1 + d)`
+/// so that it needs no scene-specific range.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Depth;
+
+impl Integrator for Depth {
+    fn ray_color(
+        &self,
+        ray: &Ray,
+        world: &dyn Hittable,
+        _lights: &[Box<dyn Hittable + Send + Sync>],
+        _background: Background,
+        rng: &mut dyn RngCore,
+    ) -> Color {
+        match world.hit(ray, 0.001, f64::INFINITY, rng) {
+            Some(record) => {
+                let shade = 1.0 / (1.0 + record.t * ray.direction.length());
+                Color::new(shade, shade, shade)
+            }
+            None => Color::default(),
+        }
+    }
+}
+
+/// A flat color per material, derived from `Material::id`. Materials with
+/// the same ID, such as one named material that a scene file shares between
+/// objects, share a color; untagged materials all have ID 0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MaterialIds;
+
+impl Integrator for MaterialIds {
+    fn ray_color(
+        &self,
+        ray: &Ray,
+        world: &dyn Hittable,
+        _lights: &[Box<dyn Hittable + Send + Sync>],
+        _background: Background,
+        rng: &mut dyn RngCore,
+    ) -> Color {
+        match world.hit(ray, 0.001, f64::INFINITY, rng) {
+            Some(record) => id_color(record.material.id()),
+            None => Color::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable::sphere::Sphere;
+    use crate::material::lambertian::Lambertian;
+    use crate::material::tagged::TaggedMaterial;
+    use crate::vec3::Point3;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_debug_views() {
+        let shared = Arc::new(TaggedMaterial::new(
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+            1,
+        ));
+        let world: Vec<Box<dyn Hittable + Send + Sync>> = vec![
+            Box::new(Sphere::new(
+                Point3::new(-2.0, 0.0, 0.0),
+                1.0,
+                Arc::clone(&shared),
+            )),
+            Box::new(Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, shared)),
+            Box::new(Sphere::new(
+                Point3::new(2.0, 0.0, 0.0),
+                1.0,
+                TaggedMaterial::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)), 2),
+            )),
+        ];
+        let towards = |x: f64| Ray::new(Point3::new(x, 0.0, 5.0), Vec3::new(0.0, 0.0, -2.0));
+        let background = Background::Sky;
+        let view = |integrator: &dyn Integrator, x: f64| {
+            let mut rng = StdRng::seed_from_u64(0);
+            integrator.ray_color(&towards(x), &world, &[], background, &mut rng)
+        };
+
+        assert_eq!(Color::new(0.5, 0.5, 1.0), view(&Normals, 0.0));
+        assert!(approx_eq!(f64, 0.2, view(&Depth, 0.0).x, epsilon = 1e-12));
+        assert_eq!(Color::default(), view(&Depth, 10.0));
+
+        assert_eq!(view(&MaterialIds, -2.0), view(&MaterialIds, 0.0));
+        assert_ne!(view(&MaterialIds, 0.0), view(&MaterialIds, 2.0));
+    }
+}
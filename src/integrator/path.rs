@@ -0,0 +1,264 @@
+use crate::hittable::Hittable;
+use crate::integrator::Integrator;
+use crate::material::Sampling;
+use crate::pdf::{HittablePdf, Pdf};
+use crate::ray::Ray;
+use crate::render::Background;
+use crate::vec3::Color;
+use rand::{Rng, RngCore};
+
+/// Power heuristic weight for a sample drawn with density `pdf`, when `other`
+/// is the density the other strategy would have drawn it with.
+fn power_heuristic(pdf: f64, other: f64) -> f64 {
+    let (a, b) = (pdf * pdf, other * other);
+    if a + b > 0.0 {
+        a / (a + b)
+    } else {
+        0.0
+    }
+}
+
+/// Bounces after which paths may be ended by Russian roulette.
+const ROULETTE_MIN_BOUNCES: i32 = 3;
+
+/// Unbiased path tracing, following at most `depth` bounces.
+///
+/// Diffuse bounces sample `lights` directly (next-event estimation) as well
+/// as the material, and weight both with multiple importance sampling, so
+/// small lights no longer show up only when a bounce happens to find them.
+/// Emissive objects left out of `lights` are still found by the material
+/// samples, just with more noise.
+///
+/// After a few bounces, paths that carry little light are ended at random
+/// (Russian roulette), and the survivors are weighted up to keep the estimate
+/// unbiased.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PathTracer {
+    pub depth: i32,
+}
+
+impl PathTracer {
+    pub fn new(depth: i32) -> Self {
+        PathTracer { depth }
+    }
+}
+
+impl Integrator for PathTracer {
+    fn ray_color(
+        &self,
+        ray: &Ray,
+        world: &dyn Hittable,
+        lights: &[Box<dyn Hittable + Send + Sync>],
+        background: Background,
+        rng: &mut dyn RngCore,
+    ) -> Color {
+        let mut color = Color::default();
+        // Product of the f * cos / p factors along the path so far.
+        let mut throughput = Color::new(1.0, 1.0, 1.0);
+        // MIS weight for light emitted by the next surface hit, which is below
+        // one when the path got there by sampling a material that also sampled
+        // lights.
+        let mut emission_weight = 1.0;
+        let mut ray = *ray;
+
+        for bounce in 0..self.depth {
+            // The background is not in the light list, so material samples are
+            // the only ones that see it and they keep their full weight.
+            let record = match world.hit(&ray, 0.001, f64::INFINITY, rng) {
+                Some(record) => record,
+                None => {
+                    color += throughput * background.color(&ray);
+                    break;
+                }
+            };
+
+            color += emission_weight * throughput * record.material.emitted(&record);
+            let scatter = match record.material.scatter(ray, &record, rng) {
+                Some(scatter) => scatter,
+                None => break,
+            };
+
+            ray = match scatter.sampling {
+                Sampling::Specular(scattered) => {
+                    throughput = throughput * scatter.attenuation;
+                    emission_weight = 1.0;
+                    scattered
+                }
+                Sampling::Pdf(pdf) => {
+                    // Each term is a one-sample Monte Carlo estimate of the
+                    // rendering equation, f * cos * L / p, with the material's
+                    // density standing in for f * cos. Lights are only sampled when
+                    // the material sample could still reach them, so that the MIS
+                    // weights always add up to one.
+                    let light_pdf = HittablePdf::new(&lights, record.p);
+                    if !lights.is_empty() && bounce + 1 < self.depth {
+                        let to_light = Ray::with_time(record.p, light_pdf.generate(rng), ray.time);
+                        let light_value = light_pdf.value(to_light.direction);
+                        let scattering_pdf = record.material.scattering_pdf(ray, &record, to_light);
+                        if light_value > 0.0 && scattering_pdf > 0.0 {
+                            if let Some(light) = world.hit(&to_light, 0.001, f64::INFINITY, rng) {
+                                let weight =
+                                    power_heuristic(light_value, pdf.value(to_light.direction));
+                                color += weight * scattering_pdf / light_value
+                                    * throughput
+                                    * scatter.attenuation
+                                    * light.material.emitted(&light);
+                            }
+                        }
+                    }
+
+                    let scattered = Ray::with_time(record.p, pdf.generate(rng), ray.time);
+                    let pdf_value = pdf.value(scattered.direction);
+                    if pdf_value <= 0.0 {
+                        break;
+                    }
+                    let scattering_pdf = record.material.scattering_pdf(ray, &record, scattered);
+                    throughput = scattering_pdf / pdf_value * throughput * scatter.attenuation;
+                    emission_weight =
+                        power_heuristic(pdf_value, light_pdf.value(scattered.direction));
+                    scattered
+                }
+            };
+
+            if bounce + 1 >= ROULETTE_MIN_BOUNCES {
+                let survival = throughput.x.max(throughput.y).max(throughput.z).min(1.0);
+                if rng.gen::<f64>() >= survival {
+                    break;
+                }
+                throughput /= survival;
+            }
+        }
+        color
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable::quad::Quad;
+    use crate::hittable::sphere::Sphere;
+    use crate::hittable::HitRecord;
+    use crate::material::diffuse_light::DiffuseLight;
+    use crate::material::lambertian::Lambertian;
+    use crate::material::{Material, Scatter};
+    use crate::vec3::{Point3, Vec3};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::f64::consts::PI;
+
+    /// A diffuse surface that also glows.
+    struct Furnace(Lambertian);
+
+    impl Material for Furnace {
+        fn scatter(
+            &self,
+            ray_in: Ray,
+            hit_record: &HitRecord,
+            rng: &mut dyn RngCore,
+        ) -> Option<Scatter> {
+            self.0.scatter(ray_in, hit_record, rng)
+        }
+
+        fn scattering_pdf(&self, ray_in: Ray, hit_record: &HitRecord, scattered: Ray) -> f64 {
+            self.0.scattering_pdf(ray_in, hit_record, scattered)
+        }
+
+        fn emitted(&self, _hit_record: &HitRecord) -> Color {
+            Color::new(1.0, 1.0, 1.0)
+        }
+    }
+
+    #[test]
+    fn test_light_sampling_matches_direct_lighting() {
+        let light = || {
+            Quad::new(
+                Point3::new(-0.5, 1.0, -0.5),
+                Vec3::new(1.0, 0.0, 0.0),
+                Vec3::new(0.0, 0.0, 1.0),
+                DiffuseLight::new(Color::new(1.0, 1.0, 1.0)),
+            )
+        };
+        let world: Vec<Box<dyn Hittable + Send + Sync>> = vec![
+            Box::new(Quad::new(
+                Point3::new(-5.0, 0.0, -5.0),
+                Vec3::new(0.0, 0.0, 10.0),
+                Vec3::new(10.0, 0.0, 0.0),
+                Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+            )),
+            Box::new(light()),
+        ];
+        let lights: Vec<Box<dyn Hittable + Send + Sync>> = vec![Box::new(light())];
+
+        // Radiance leaving the floor below the light centre towards the eye is
+        // albedo / pi times the integral of cos * cos' / d^2 over the light.
+        let n = 200;
+        let mut integral = 0.0;
+        for i in 0..n {
+            for j in 0..n {
+                let x = -0.5 + (i as f64 + 0.5) / n as f64;
+                let z = -0.5 + (j as f64 + 0.5) / n as f64;
+                let d2 = x * x + 1.0 + z * z;
+                integral += 1.0 / (d2 * d2) / (n * n) as f64;
+            }
+        }
+        let expected = 0.5 / PI * integral;
+
+        let ray = Ray::new(Point3::new(0.0, 0.5, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let mut rng = StdRng::seed_from_u64(1);
+        let samples = 20_000;
+        let total: Color = (0..samples)
+            .map(|_| {
+                PathTracer::new(2).ray_color(
+                    &ray,
+                    &world,
+                    &lights,
+                    Background::Solid(Color::default()),
+                    &mut rng,
+                )
+            })
+            .sum();
+        let actual = total.x / samples as f64;
+        assert!(
+            (actual - expected).abs() < 0.02 * expected,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_russian_roulette_is_unbiased() {
+        // Inside a closed sphere whose walls all emit 1 and reflect a fraction
+        // a, the radiance gathered over n bounces is 1 + a + ... + a^(n-1).
+        let albedo = 0.5;
+        let world: Vec<Box<dyn Hittable + Send + Sync>> = vec![Box::new(Sphere::new(
+            Point3::default(),
+            -1.0,
+            Furnace(Lambertian::new(Color::new(albedo, albedo, albedo))),
+        ))];
+        let ray = Ray::new(Point3::default(), Vec3::new(1.0, 0.0, 0.0));
+        let depth = 12;
+        let expected: f64 = (0..depth).map(|n| albedo.powi(n)).sum();
+
+        let mut rng = StdRng::seed_from_u64(2);
+        let samples = 20_000;
+        let total: Color = (0..samples)
+            .map(|_| {
+                PathTracer::new(depth).ray_color(
+                    &ray,
+                    &world,
+                    &[],
+                    Background::Solid(Color::default()),
+                    &mut rng,
+                )
+            })
+            .sum();
+        let actual = total.x / samples as f64;
+        assert!(
+            (actual - expected).abs() < 0.02 * expected,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use crate::hittable::{HitRecord, Hittable};
+use crate::integrator::Integrator;
+use crate::material::{Sampling, Scatter};
+use crate::pdf::{HittablePdf, Pdf};
+use crate::ray::Ray;
+use crate::render::Background;
+use crate::vec3::Color;
+use rand::RngCore;
+
+/// A Whitted-style ray tracer: mirror and glass bounces are followed
+/// recursively, up to `depth` of them, but diffuse surfaces only gather light
+/// directly from the light list, plus the background along their normal as an
+/// ambient term. Much faster than path tracing and much less noisy, at the
+/// cost of all diffuse interreflection.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Whitted {
+    pub depth: i32,
+}
+
+impl Whitted {
+    pub fn new(depth: i32) -> Self {
+        Whitted { depth }
+    }
+
+    fn trace(
+        &self,
+        ray: &Ray,
+        world: &dyn Hittable,
+        lights: &[Box<dyn Hittable + Send + Sync>],
+        background: Background,
+        depth: i32,
+        rng: &mut dyn RngCore,
+    ) -> Color {
+        if depth <= 0 {
+            return Color::default();
+        }
+
+        let record = match world.hit(ray, 0.001, f64::INFINITY, rng) {
+            Some(record) => record,
+            None => return background.color(ray),
+        };
+
+        let emitted = record.material.emitted(&record);
+        let scatter = match record.material.scatter(*ray, &record, rng) {
+            Some(scatter) => scatter,
+            None => return emitted,
+        };
+
+        match scatter.sampling {
+            Sampling::Specular(scattered) => {
+                emitted
+                    + scatter.attenuation
+                        * self.trace(&scattered, world, lights, background, depth - 1, rng)
+            }
+            Sampling::Pdf(_) => {
+                let ambient = background.color(&Ray::with_time(record.p, record.normal, ray.time));
+                emitted
+                    + scatter.attenuation * ambient
+                    + direct_light(ray, &record, &scatter, world, lights, rng)
+            }
+        }
+    }
+}
+
+/// One-sample estimate of the light reaching a diffuse surface straight from
+/// `lights`, with shadows.
+fn direct_light(
+    ray: &Ray,
+    record: &HitRecord,
+    scatter: &Scatter,
+    world: &dyn Hittable,
+    lights: &[Box<dyn Hittable + Send + Sync>],
+    rng: &mut dyn RngCore,
+) -> Color {
+    if lights.is_empty() {
+        return Color::default();
+    }
+    let light_pdf = HittablePdf::new(&lights, record.p);
+    let to_light = Ray::with_time(record.p, light_pdf.generate(rng), ray.time);
+    let light_value = light_pdf.value(to_light.direction);
+    if light_value <= 0.0 {
+        return Color::default();
+    }
+    let scattering_pdf = record.material.scattering_pdf(*ray, record, to_light);
+    match world.hit(&to_light, 0.001, f64::INFINITY, rng) {
+        Some(light) => {
+            scattering_pdf / light_value * scatter.attenuation * light.material.emitted(&light)
+        }
+        None => Color::default(),
+    }
+}
+
+impl Integrator for Whitted {
+    fn ray_color(
+        &self,
+        ray: &Ray,
+        world: &dyn Hittable,
+        lights: &[Box<dyn Hittable + Send + Sync>],
+        background: Background,
+        rng: &mut dyn RngCore,
+    ) -> Color {
+        self.trace(ray, world, lights, background, self.depth, rng)
+    }
+}
@@ -0,0 +1,27 @@
+pub mod aabb;
+pub mod aov;
+pub mod camera;
+pub mod denoise;
+pub mod hittable;
+pub mod integrator;
+pub mod material;
+pub mod obj;
+pub mod onb;
+pub mod output;
+pub mod pdf;
+pub mod ray;
+pub mod render;
+pub mod scene;
+pub mod texture;
+pub mod tone_map;
+pub mod transform;
+pub mod vec3;
+pub mod world;
+
+#[allow(unused_imports)]
+#[macro_use]
+extern crate float_cmp;
+extern crate indicatif;
+extern crate rand;
+extern crate serde;
+extern crate toml;
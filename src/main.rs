@@ -1,162 +1,139 @@
-mod camera;
-mod hittable;
-mod material;
-mod ray;
-mod vec3;
-mod world;
+mod cli;
 
-#[allow(unused_imports)]
-#[macro_use]
-extern crate float_cmp;
+extern crate clap;
 extern crate image;
-extern crate indicatif;
 extern crate rand;
-
-use crate::camera::Camera;
-use crate::hittable::Hittable;
-use crate::ray::Ray;
-use crate::vec3::{Color, Point3, Vec3};
-
-use image::{ImageBuffer, Rgb};
-use indicatif::{ParallelProgressIterator, ProgressBar, ProgressStyle};
-use rand::prelude::ThreadRng;
-use rand::Rng;
-use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+extern crate rayon;
+
+use crate::cli::{BuiltInScene, Cli};
+use ray_tracing_in_one_weekend::aov::Aov;
+use ray_tracing_in_one_weekend::denoise::{denoise, DenoiseParams, Features};
+use ray_tracing_in_one_weekend::hittable::bvh::BvhNode;
+use ray_tracing_in_one_weekend::integrator::IntegratorKind;
+use ray_tracing_in_one_weekend::output::write_image;
+use ray_tracing_in_one_weekend::render::{render_image, RenderParams};
+use ray_tracing_in_one_weekend::scene::{load_scene, Scene};
+use ray_tracing_in_one_weekend::tone_map::DisplayTransform;
+
+use clap::Parser;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use std::error::Error;
-
-#[derive(Clone, Copy)]
-struct Bounds {
-    width: u32,
-    height: u32,
-}
-
-#[derive(Clone, Copy)]
-struct RenderParams {
-    samples_per_pixel: usize,
-    depth: i32,
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
 }
 
-fn main() -> Result<(), Box<dyn Error + Sync + Send>> {
-    let aspect_ratio = 16.0 / 9.0;
+fn run(cli: Cli) -> Result<(), Box<dyn Error + Sync + Send>> {
+    if let Some(threads) = cli.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()?;
+    }
 
-    let bounds = Bounds {
-        width: 3840,
-        height: (3840.0 / aspect_ratio) as u32,
+    let scene = match &cli.scene_file {
+        Some(path) => {
+            let scene = load_scene(path)?;
+            let bounds = cli
+                .bounds(Some(scene.bounds))
+                .unwrap_or_else(|err| err.exit());
+            Scene { bounds, ..scene }
+        }
+        None => {
+            let aspect_ratio = cli.aspect_ratio.unwrap_or(cli::DEFAULT_ASPECT_RATIO);
+            let bounds = cli.bounds(None).unwrap_or_else(|err| err.exit());
+
+            let seed = cli.seed.unwrap_or(0);
+            let mut rng = StdRng::seed_from_u64(seed);
+            let scene = cli.scene.unwrap_or(BuiltInScene::BookCover);
+            let (world, lights, camera) = scene.build(aspect_ratio, &mut rng);
+
+            let params = RenderParams {
+                samples_per_pixel: cli::DEFAULT_SAMPLES_PER_PIXEL,
+                depth: cli::DEFAULT_DEPTH,
+                background: scene.background(),
+                integrator: IntegratorKind::default(),
+                seed,
+                adaptive: None,
+            };
+            Scene {
+                bounds,
+                params,
+                display: DisplayTransform::default(),
+                aovs: Vec::new(),
+                denoise: false,
+                camera,
+                world,
+                lights,
+            }
+        }
     };
 
-    let world = world::book_cover_scene();
-
-    let camera = Camera::new(
-        Point3::new(13.0, 2.0, 3.0),
-        Point3::new(0.0, 0.0, 0.0),
-        Vec3::new(0.0, 1.0, 0.0),
-        20.0,
-        aspect_ratio,
-        0.1,
-        10.0,
-    );
-
     let params = RenderParams {
-        samples_per_pixel: 500,
-        depth: 50,
+        samples_per_pixel: cli
+            .samples_per_pixel
+            .unwrap_or(scene.params.samples_per_pixel),
+        depth: cli.depth.unwrap_or(scene.params.depth),
+        integrator: cli.integrator(scene.params.integrator),
+        seed: cli.seed.unwrap_or(scene.params.seed),
+        adaptive: cli.adaptive(scene.params.adaptive),
+        ..scene.params
     };
 
-    let pixels = render_image(bounds, &world, camera, params)?;
-    write_image(pixels, bounds, params);
-
-    Ok(())
-}
-
-fn write_image(pixels: Vec<Color>, bounds: Bounds, params: RenderParams) {
-    let mut image_buf: ImageBuffer<Rgb<u8>, Vec<u8>> =
-        ImageBuffer::new(bounds.width, bounds.height);
-
-    for (i, pixel) in pixels.iter().enumerate() {
-        let scale = 1.0 / params.samples_per_pixel as f64;
-
-        // Divide the color by the number of samples and gamma-correct for gamma=2.0
-        let (r, g, b) = (
-            (pixel.x * scale).sqrt(),
-            (pixel.y * scale).sqrt(),
-            (pixel.z * scale).sqrt(),
-        );
-
-        let ir = (256.0 * clamp(r, 0.0, 0.999)) as u8;
-        let ig = (256.0 * clamp(g, 0.0, 0.999)) as u8;
-        let ib = (256.0 * clamp(b, 0.0, 0.999)) as u8;
-
-        let x_coord = i as u32 % bounds.width;
-        let y_coord = i as u32 / bounds.width;
-
-        let pixel = image_buf.get_pixel_mut(x_coord, y_coord);
-        *pixel = image::Rgb([ir, ig, ib]);
-    }
-
-    image_buf
-        .save("image.png")
-        .expect("Image rendered, but failed to save!");
-}
-
-fn render_image(
-    bounds: Bounds,
-    world: &[Box<dyn Hittable + Send + Sync>],
-    camera: Camera,
-    params: RenderParams,
-) -> Result<Vec<Color>, Box<dyn Error + Sync + Send>> {
-    let bar = ProgressBar::new(bounds.height as u64).with_style(
-        ProgressStyle::default_bar()
-            .template("Rendering: [{eta_precise}] {bar:40.cyan/blue} {pos:>7}/{len:} scanlines"),
-    );
-
-    let pixels = (0..bounds.height)
-        .into_par_iter()
-        .rev()
-        .progress_with(bar)
-        .flat_map(|y| {
-            (0..bounds.width).into_par_iter().map(move |x| {
-                let collect_sample = |rng: &mut ThreadRng| {
-                    let u = (x as f64 + rng.gen_range(0.0, 1.0)) / (bounds.width - 1) as f64;
-                    let v = (y as f64 + rng.gen_range(0.0, 1.0)) / (bounds.height - 1) as f64;
-
-                    let ray = camera.get_ray(u, v);
-                    ray_color(&ray, &world, params.depth)
-                };
-
-                let mut rng = rand::thread_rng();
-                (0..params.samples_per_pixel)
-                    .map(|_| collect_sample(&mut rng))
-                    .sum()
-            })
-        })
-        .collect();
-    Ok(pixels)
-}
-
-fn ray_color(ray: &Ray, world: &[Box<dyn Hittable + Send + Sync>], depth: i32) -> Color {
-    // If we've exceeded the ray bounce limit, no more light is gathered.
-    // This is the recursion base-case.
-    if depth <= 0 {
-        return Color::default();
-    }
-
-    if let Some(record) = world.hit(ray, 0.001, f64::INFINITY) {
-        if let Some(scatter) = record.material.scatter(*ray, &record) {
-            return scatter.attenuation * ray_color(&scatter.ray, world, depth - 1);
+    let world = BvhNode::new(scene.world);
+    let integrator = params.integrator.build(params.depth);
+
+    // The denoiser needs some AOVs whether or not they are written out.
+    let aovs = cli.aovs(&scene.aovs);
+    let denoising = cli.denoise || scene.denoise;
+    let mut rendered_aovs = aovs.clone();
+    if denoising {
+        for &aov in [Aov::Albedo, Aov::Normal, Aov::Depth].iter() {
+            if !rendered_aovs.contains(&aov) {
+                rendered_aovs.push(aov);
+            }
         }
     }
 
-    let unit_direction = ray.direction.unit_vector();
-    let t = 0.5 * (unit_direction.y + 1.0);
-    (1.0 - t) * Color::new(1.0, 1.0, 1.0) + t * Color::new(0.5, 0.7, 1.0)
-}
-
-fn clamp<T: PartialOrd>(input: T, min: T, max: T) -> T {
-    assert!(min <= max);
-    if input < min {
-        return min;
+    let frame = render_image(
+        scene.bounds,
+        &world,
+        &scene.lights,
+        scene.camera,
+        integrator.as_ref(),
+        params,
+        &rendered_aovs,
+    )?;
+    let mut pixels = frame.average();
+    if denoising {
+        let features = Features {
+            albedo: frame.aov(Aov::Albedo).unwrap(),
+            normal: frame.aov(Aov::Normal).unwrap(),
+            depth: frame.aov(Aov::Depth).unwrap(),
+        };
+        pixels = denoise(&pixels, scene.bounds, &features, DenoiseParams::default());
     }
-    if input > max {
-        return max;
-    }
-    input
+    let outputs: Vec<_> = frame
+        .aovs
+        .into_iter()
+        .filter(|(aov, _)| aovs.contains(aov))
+        .collect();
+    write_image(
+        &cli.output,
+        cli.format(),
+        scene.bounds,
+        &pixels,
+        &outputs,
+        cli.display(scene.display),
+    )?;
+
+    Ok(())
 }
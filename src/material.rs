@@ -1,16 +1,89 @@
 pub mod dielectric;
+pub mod diffuse_light;
+pub mod isotropic;
 pub mod lambertian;
 pub mod metal;
+pub mod tagged;
 
 use crate::hittable::HitRecord;
+use crate::pdf::Pdf;
 use crate::ray::Ray;
 use crate::vec3::Color;
+use rand::RngCore;
+use std::sync::Arc;
 
 pub struct Scatter {
-    pub ray: Ray,
     pub attenuation: Color,
+    pub sampling: Sampling,
+}
+
+/// How the direction of a scattered ray is chosen.
+pub enum Sampling {
+    /// A mirror-like bounce in a single direction, with no density to weigh.
+    Specular(Ray),
+    /// A random direction drawn from a density. The integrator may draw from a
+    /// different density instead, as long as it weighs the result by
+    /// `Material::scattering_pdf`.
+    Pdf(Box<dyn Pdf>),
 }
 
 pub trait Material {
-    fn scatter(&self, ray_in: Ray, hit_record: &HitRecord) -> Option<Scatter>;
+    fn scatter(
+        &self,
+        ray_in: Ray,
+        hit_record: &HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<Scatter>;
+
+    /// Density with which a non-specular material scatters `ray_in` into
+    /// `scattered`, i.e. its BRDF times the cosine term.
+    fn scattering_pdf(&self, _ray_in: Ray, _hit_record: &HitRecord, _scattered: Ray) -> f64 {
+        0.0
+    }
+
+    /// Light given off at the hit point. Only light sources emit anything.
+    fn emitted(&self, _hit_record: &HitRecord) -> Color {
+        Color::default()
+    }
+
+    /// Base color at the hit point, for the albedo AOV. Black for materials
+    /// that only emit.
+    fn albedo(&self, _hit_record: &HitRecord) -> Color {
+        Color::default()
+    }
+
+    /// Stable ID for the material ID AOV and debug view, or 0 if it has
+    /// none. Set by `TaggedMaterial`.
+    fn id(&self) -> u32 {
+        0
+    }
+}
+
+/// Lets one material be shared between several objects, e.g. materials that a
+/// scene file defines once by name.
+impl<M: Material + ?Sized> Material for Arc<M> {
+    fn scatter(
+        &self,
+        ray_in: Ray,
+        hit_record: &HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<Scatter> {
+        (**self).scatter(ray_in, hit_record, rng)
+    }
+
+    fn scattering_pdf(&self, ray_in: Ray, hit_record: &HitRecord, scattered: Ray) -> f64 {
+        (**self).scattering_pdf(ray_in, hit_record, scattered)
+    }
+
+    fn emitted(&self, hit_record: &HitRecord) -> Color {
+        (**self).emitted(hit_record)
+    }
+
+    fn albedo(&self, hit_record: &HitRecord) -> Color {
+        (**self).albedo(hit_record)
+    }
+
+    fn id(&self) -> u32 {
+        (**self).id()
+    }
 }
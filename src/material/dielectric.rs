@@ -1,8 +1,8 @@
 use crate::hittable::HitRecord;
-use crate::material::{Material, Scatter};
+use crate::material::{Material, Sampling, Scatter};
 use crate::ray::Ray;
 use crate::vec3::{Color, Vec3};
-use rand::{thread_rng, Rng};
+use rand::{Rng, RngCore};
 
 #[derive(Clone, Copy)]
 pub struct Dielectric {
@@ -16,13 +16,18 @@ impl Dielectric {
 
     fn reflectance(cosine: f64, ref_idx: f64) -> f64 {
         // Schlick's approximation for reflectance.
-        let r0 = (1.0 - ref_idx) / (1.0 + ref_idx).powi(2);
+        let r0 = ((1.0 - ref_idx) / (1.0 + ref_idx)).powi(2);
         r0 + (1.0 - r0) * (1.0 - cosine).powi(5)
     }
 }
 
 impl Material for Dielectric {
-    fn scatter(&self, ray_in: Ray, hit_record: &HitRecord) -> Option<Scatter> {
+    fn scatter(
+        &self,
+        ray_in: Ray,
+        hit_record: &HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<Scatter> {
         let attenuation = Color::new(1.0, 1.0, 1.0);
         let refraction_ratio = if hit_record.front_face {
             1.0 / self.ir
@@ -34,7 +39,6 @@ impl Material for Dielectric {
         let cos_theta = f64::min(-unit_direction.dot(hit_record.normal), 1.0);
         let sin_theta = (1.0 - cos_theta.powi(2)).sqrt();
         let cannot_refract = refraction_ratio * sin_theta > 1.0;
-        let mut rng = thread_rng();
         let schlick_approx =
             Dielectric::reflectance(cos_theta, refraction_ratio) > rng.gen_range(0.0, 1.0);
 
@@ -44,7 +48,40 @@ impl Material for Dielectric {
             unit_direction.refract(hit_record.normal, refraction_ratio)
         };
 
-        let ray = Ray::new(hit_record.p, direction);
-        Some(Scatter { ray, attenuation })
+        let ray = Ray::with_time(hit_record.p, direction, ray_in.time);
+        Some(Scatter {
+            attenuation,
+            sampling: Sampling::Specular(ray),
+        })
+    }
+
+    /// Clear glass passes on all the color behind it.
+    fn albedo(&self, _hit_record: &HitRecord) -> Color {
+        Color::new(1.0, 1.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_cmp::approx_eq;
+
+    #[test]
+    fn test_reflectance() {
+        // Glass reflects 4% of light at normal incidence, from either side.
+        assert!(approx_eq!(
+            f64,
+            Dielectric::reflectance(1.0, 1.5),
+            0.04,
+            epsilon = 1e-12
+        ));
+        assert!(approx_eq!(
+            f64,
+            Dielectric::reflectance(1.0, 1.0 / 1.5),
+            0.04,
+            epsilon = 1e-12
+        ));
+        assert!(approx_eq!(f64, Dielectric::reflectance(0.0, 1.5), 1.0));
+        assert!(approx_eq!(f64, Dielectric::reflectance(1.0, 1.0), 0.0));
     }
 }
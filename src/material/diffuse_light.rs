@@ -0,0 +1,42 @@
+use crate::hittable::HitRecord;
+use crate::material::{Material, Scatter};
+use crate::ray::Ray;
+use crate::texture::solid_color::SolidColor;
+use crate::texture::Texture;
+use crate::vec3::Color;
+use rand::RngCore;
+
+/// Emits light equally in every direction and never scatters.
+#[derive(Clone, Copy)]
+pub struct DiffuseLight<T: Texture = SolidColor> {
+    pub emit: T,
+}
+
+impl DiffuseLight {
+    pub fn new(emit: Color) -> Self {
+        DiffuseLight {
+            emit: SolidColor::new(emit),
+        }
+    }
+}
+
+impl<T: Texture> DiffuseLight<T> {
+    pub fn textured(emit: T) -> Self {
+        DiffuseLight { emit }
+    }
+}
+
+impl<T: Texture> Material for DiffuseLight<T> {
+    fn scatter(
+        &self,
+        _ray_in: Ray,
+        _hit_record: &HitRecord,
+        _rng: &mut dyn RngCore,
+    ) -> Option<Scatter> {
+        None
+    }
+
+    fn emitted(&self, hit_record: &HitRecord) -> Color {
+        self.emit.value(hit_record.u, hit_record.v, hit_record.p)
+    }
+}
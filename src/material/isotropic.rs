@@ -0,0 +1,53 @@
+use crate::hittable::HitRecord;
+use crate::material::{Material, Sampling, Scatter};
+use crate::pdf::SpherePdf;
+use crate::ray::Ray;
+use crate::texture::solid_color::SolidColor;
+use crate::texture::Texture;
+use crate::vec3::Color;
+use rand::RngCore;
+use std::f64::consts::PI;
+
+/// Phase function of a participating medium: scatters uniformly in every
+/// direction, ignoring the (meaningless) surface normal.
+#[derive(Clone, Copy)]
+pub struct Isotropic<T: Texture = SolidColor> {
+    pub albedo: T,
+}
+
+impl Isotropic {
+    pub fn new(albedo: Color) -> Self {
+        Isotropic {
+            albedo: SolidColor::new(albedo),
+        }
+    }
+}
+
+impl<T: Texture> Isotropic<T> {
+    pub fn textured(albedo: T) -> Self {
+        Isotropic { albedo }
+    }
+}
+
+impl<T: Texture> Material for Isotropic<T> {
+    fn scatter(
+        &self,
+        _ray_in: Ray,
+        hit_record: &HitRecord,
+        _rng: &mut dyn RngCore,
+    ) -> Option<Scatter> {
+        let attenuation = self.albedo.value(hit_record.u, hit_record.v, hit_record.p);
+        Some(Scatter {
+            attenuation,
+            sampling: Sampling::Pdf(Box::new(SpherePdf)),
+        })
+    }
+
+    fn scattering_pdf(&self, _ray_in: Ray, _hit_record: &HitRecord, _scattered: Ray) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+
+    fn albedo(&self, hit_record: &HitRecord) -> Color {
+        self.albedo.value(hit_record.u, hit_record.v, hit_record.p)
+    }
+}
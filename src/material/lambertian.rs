@@ -1,29 +1,52 @@
 use crate::hittable::HitRecord;
-use crate::material::{Material, Scatter};
+use crate::material::{Material, Sampling, Scatter};
+use crate::pdf::CosinePdf;
 use crate::ray::Ray;
-use crate::vec3::{Color, Vec3};
+use crate::texture::solid_color::SolidColor;
+use crate::texture::Texture;
+use crate::vec3::Color;
+use rand::RngCore;
+use std::f64::consts::PI;
 
 #[derive(Clone, Copy)]
-pub struct Lambertian {
-    pub albedo: Color,
+pub struct Lambertian<T: Texture = SolidColor> {
+    pub albedo: T,
 }
 
 impl Lambertian {
     pub fn new(albedo: Color) -> Self {
+        Lambertian {
+            albedo: SolidColor::new(albedo),
+        }
+    }
+}
+
+impl<T: Texture> Lambertian<T> {
+    pub fn textured(albedo: T) -> Self {
         Lambertian { albedo }
     }
 }
 
-impl Material for Lambertian {
-    fn scatter(&self, _ray_in: Ray, hit_record: &HitRecord) -> Option<Scatter> {
-        let mut scatter_direction = hit_record.normal + Vec3::random_unit_vector();
+impl<T: Texture> Material for Lambertian<T> {
+    fn scatter(
+        &self,
+        _ray_in: Ray,
+        hit_record: &HitRecord,
+        _rng: &mut dyn RngCore,
+    ) -> Option<Scatter> {
+        let attenuation = self.albedo.value(hit_record.u, hit_record.v, hit_record.p);
+        Some(Scatter {
+            attenuation,
+            sampling: Sampling::Pdf(Box::new(CosinePdf::new(hit_record.normal))),
+        })
+    }
 
-        if scatter_direction.near_zero() {
-            scatter_direction = hit_record.normal;
-        }
+    fn scattering_pdf(&self, _ray_in: Ray, hit_record: &HitRecord, scattered: Ray) -> f64 {
+        let cosine = hit_record.normal.dot(scattered.direction.unit_vector());
+        (cosine / PI).max(0.0)
+    }
 
-        let ray = Ray::new(hit_record.p, scatter_direction);
-        let attenuation = self.albedo;
-        Some(Scatter { ray, attenuation })
+    fn albedo(&self, hit_record: &HitRecord) -> Color {
+        self.albedo.value(hit_record.u, hit_record.v, hit_record.p)
     }
 }
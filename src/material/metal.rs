@@ -1,33 +1,55 @@
 use crate::hittable::HitRecord;
-use crate::material::{Material, Scatter};
+use crate::material::{Material, Sampling, Scatter};
 use crate::ray::Ray;
+use crate::texture::solid_color::SolidColor;
+use crate::texture::Texture;
 use crate::vec3::{Color, Vec3};
+use rand::RngCore;
 
 #[derive(Clone, Copy)]
-pub struct Metal {
-    pub albedo: Color,
+pub struct Metal<T: Texture = SolidColor> {
+    pub albedo: T,
     pub fuzz: f64,
 }
 
 impl Metal {
     pub fn new(albedo: Color, fuzz: f64) -> Self {
+        Metal::textured(SolidColor::new(albedo), fuzz)
+    }
+}
+
+impl<T: Texture> Metal<T> {
+    pub fn textured(albedo: T, fuzz: f64) -> Self {
         let fuzz = if fuzz < 1.0 { fuzz } else { 1.0 };
         Metal { albedo, fuzz }
     }
 }
 
-impl Material for Metal {
-    fn scatter(&self, ray_in: Ray, hit_record: &HitRecord) -> Option<Scatter> {
+impl<T: Texture> Material for Metal<T> {
+    fn scatter(
+        &self,
+        ray_in: Ray,
+        hit_record: &HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<Scatter> {
         let reflected = Vec3::unit_vector(ray_in.direction).reflect(hit_record.normal);
-        let ray = Ray::new(
+        let ray = Ray::with_time(
             hit_record.p,
-            reflected + self.fuzz * Vec3::random_in_unit_sphere(),
+            reflected + self.fuzz * Vec3::random_in_unit_sphere(rng),
+            ray_in.time,
         );
-        let attenuation = self.albedo;
+        let attenuation = self.albedo.value(hit_record.u, hit_record.v, hit_record.p);
         if ray.direction.dot(hit_record.normal) > 0.0 {
-            Some(Scatter { ray, attenuation })
+            Some(Scatter {
+                attenuation,
+                sampling: Sampling::Specular(ray),
+            })
         } else {
             None
         }
     }
+
+    fn albedo(&self, hit_record: &HitRecord) -> Color {
+        self.albedo.value(hit_record.u, hit_record.v, hit_record.p)
+    }
 }
@@ -0,0 +1,46 @@
+use crate::hittable::HitRecord;
+use crate::material::{Material, Scatter};
+use crate::ray::Ray;
+use crate::vec3::Color;
+use rand::RngCore;
+
+/// Gives a material a stable ID for the material ID AOV and debug view, and
+/// otherwise behaves exactly like it.
+#[derive(Clone, Copy)]
+pub struct TaggedMaterial<M: Material> {
+    material: M,
+    id: u32,
+}
+
+impl<M: Material> TaggedMaterial<M> {
+    pub fn new(material: M, id: u32) -> Self {
+        TaggedMaterial { material, id }
+    }
+}
+
+impl<M: Material> Material for TaggedMaterial<M> {
+    fn scatter(
+        &self,
+        ray_in: Ray,
+        hit_record: &HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<Scatter> {
+        self.material.scatter(ray_in, hit_record, rng)
+    }
+
+    fn scattering_pdf(&self, ray_in: Ray, hit_record: &HitRecord, scattered: Ray) -> f64 {
+        self.material.scattering_pdf(ray_in, hit_record, scattered)
+    }
+
+    fn emitted(&self, hit_record: &HitRecord) -> Color {
+        self.material.emitted(hit_record)
+    }
+
+    fn albedo(&self, hit_record: &HitRecord) -> Color {
+        self.material.albedo(hit_record)
+    }
+
+    fn id(&self) -> u32 {
+        self.id
+    }
+}
@@ -0,0 +1,609 @@
+//! Wavefront OBJ loader with MTL material libraries.
+//!
+//! Faces are grouped by their `usemtl` material and each group becomes one
+//! [`TriangleMesh`]. Polygons are fan-triangulated. MTL materials are mapped onto
+//! the renderer's materials as follows:
+//!
+//! - `d < 1` or a transparent `illum` model (4, 6, 7, 9) becomes [`Dielectric`]
+//!   with index of refraction `Ni`.
+//! - A reflective `illum` model (3, 5, 8) becomes [`Metal`] tinted by `Ks`, with
+//!   fuzz derived from the Phong exponent `Ns`.
+//! - Everything else becomes [`Lambertian`] with albedo `Kd`.
+//!
+//! `mtllib` may name several libraries separated by spaces, or a single one
+//! whose file name contains spaces.
+
+use crate::hittable::mesh::TriangleMesh;
+use crate::hittable::Hittable;
+use crate::material::dielectric::Dielectric;
+use crate::material::lambertian::Lambertian;
+use crate::material::metal::Metal;
+use crate::vec3::{Color, Point3, Vec3};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::str::SplitWhitespace;
+
+#[derive(Debug)]
+pub enum ObjError {
+    /// The OBJ file or one of its material libraries could not be read.
+    Io { path: PathBuf, source: io::Error },
+    /// A statement could not be parsed, e.g. a face with fewer than three vertices.
+    Malformed {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+    /// A face refers to a vertex, normal or texture coordinate that does not exist.
+    BadIndex {
+        path: PathBuf,
+        line: usize,
+        index: i64,
+    },
+    /// `usemtl` names a material that no loaded library defines.
+    UnknownMaterial {
+        path: PathBuf,
+        line: usize,
+        name: String,
+    },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            ObjError::Malformed {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", path.display(), line, message),
+            ObjError::BadIndex { path, line, index } => write!(
+                f,
+                "{}:{}: index {} is out of range",
+                path.display(),
+                line,
+                index
+            ),
+            ObjError::UnknownMaterial { path, line, name } => write!(
+                f,
+                "{}:{}: unknown material `{}`",
+                path.display(),
+                line,
+                name
+            ),
+        }
+    }
+}
+
+impl Error for ObjError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ObjError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Material parameters read from an MTL `newmtl` block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MtlMaterial {
+    pub diffuse: Color,
+    pub specular: Color,
+    pub shininess: f64,
+    pub ior: f64,
+    pub dissolve: f64,
+    pub illum: u32,
+}
+
+impl Default for MtlMaterial {
+    fn default() -> Self {
+        MtlMaterial {
+            diffuse: Color::new(0.8, 0.8, 0.8),
+            specular: Color::default(),
+            shininess: 0.0,
+            ior: 1.5,
+            dissolve: 1.0,
+            illum: 2,
+        }
+    }
+}
+
+impl MtlMaterial {
+    fn build_mesh(
+        &self,
+        positions: Vec<Point3>,
+        normals: Vec<Vec3>,
+        uvs: Vec<(f64, f64)>,
+        indices: Vec<[usize; 3]>,
+    ) -> Box<dyn Hittable + Send + Sync> {
+        if self.dissolve < 1.0 || matches!(self.illum, 4 | 6 | 7 | 9) {
+            let material = Dielectric::new(self.ior);
+            Box::new(TriangleMesh::new(
+                positions, normals, uvs, indices, material,
+            ))
+        } else if matches!(self.illum, 3 | 5 | 8) {
+            // Map the Phong exponent onto a roughness, as in Walter et al. 2007.
+            let fuzz = (2.0 / (self.shininess + 2.0)).sqrt();
+            let material = Metal::new(self.specular, fuzz);
+            Box::new(TriangleMesh::new(
+                positions, normals, uvs, indices, material,
+            ))
+        } else {
+            let material = Lambertian::new(self.diffuse);
+            Box::new(TriangleMesh::new(
+                positions, normals, uvs, indices, material,
+            ))
+        }
+    }
+}
+
+/// Position, texture coordinate and normal indices of one face corner.
+type FaceVertex = (usize, Option<usize>, Option<usize>);
+
+struct FaceGroup {
+    material: Option<String>,
+    faces: Vec<[FaceVertex; 3]>,
+}
+
+/// Tracks where we are in a file so errors can point at the offending line.
+struct Context<'a> {
+    path: &'a Path,
+    line: usize,
+}
+
+impl Context<'_> {
+    fn malformed(&self, message: impl Into<String>) -> ObjError {
+        ObjError::Malformed {
+            path: self.path.to_path_buf(),
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    fn parse_f64(&self, token: Option<&str>, what: &str) -> Result<f64, ObjError> {
+        let token = token.ok_or_else(|| self.malformed(format!("missing {}", what)))?;
+        token
+            .parse()
+            .map_err(|_| self.malformed(format!("invalid {} `{}`", what, token)))
+    }
+
+    fn parse_vec3(&self, tokens: &mut SplitWhitespace, what: &str) -> Result<Vec3, ObjError> {
+        Ok(Vec3::new(
+            self.parse_f64(tokens.next(), what)?,
+            self.parse_f64(tokens.next(), what)?,
+            self.parse_f64(tokens.next(), what)?,
+        ))
+    }
+
+    /// Resolves a 1-based (or negative, relative) OBJ index into `0..count`.
+    fn resolve_index(&self, token: &str, count: usize) -> Result<usize, ObjError> {
+        let index: i64 = token
+            .parse()
+            .map_err(|_| self.malformed(format!("invalid index `{}`", token)))?;
+        let resolved = if index < 0 {
+            count as i64 + index
+        } else {
+            index - 1
+        };
+        if index == 0 || resolved < 0 || resolved >= count as i64 {
+            return Err(ObjError::BadIndex {
+                path: self.path.to_path_buf(),
+                line: self.line,
+                index,
+            });
+        }
+        Ok(resolved as usize)
+    }
+}
+
+fn open(path: &Path) -> Result<BufReader<File>, ObjError> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|source| ObjError::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+}
+
+/// Reads each line of `path`, stripped of comments, along with its line number.
+fn lines(path: &Path) -> Result<Vec<(usize, String)>, ObjError> {
+    open(path)?
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let mut line = line.map_err(|source| ObjError::Io {
+                path: path.to_path_buf(),
+                source,
+            })?;
+            if let Some(comment) = line.find('#') {
+                line.truncate(comment);
+            }
+            Ok((i + 1, line))
+        })
+        .collect()
+}
+
+/// Parses an MTL material library into materials keyed by name.
+pub fn load_mtl<P: AsRef<Path>>(path: P) -> Result<HashMap<String, MtlMaterial>, ObjError> {
+    let path = path.as_ref();
+    let mut materials = HashMap::new();
+    let mut current: Option<(String, MtlMaterial)> = None;
+
+    for (line, text) in lines(path)? {
+        let ctx = Context { path, line };
+        let mut tokens = text.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+
+        if keyword == "newmtl" {
+            let name = tokens.collect::<Vec<_>>().join(" ");
+            if name.is_empty() {
+                return Err(ctx.malformed("newmtl without a name"));
+            }
+            if let Some((name, material)) = current.take() {
+                materials.insert(name, material);
+            }
+            current = Some((name, MtlMaterial::default()));
+            continue;
+        }
+
+        let material = match current.as_mut() {
+            Some((_, material)) => material,
+            None if matches!(keyword, "Kd" | "Ks" | "Ns" | "Ni" | "d" | "Tr" | "illum") => {
+                return Err(ctx.malformed(format!("`{}` before any newmtl", keyword)))
+            }
+            None => continue,
+        };
+
+        match keyword {
+            "Kd" => material.diffuse = ctx.parse_vec3(&mut tokens, "color")?,
+            "Ks" => material.specular = ctx.parse_vec3(&mut tokens, "color")?,
+            "Ns" => material.shininess = ctx.parse_f64(tokens.next(), "exponent")?,
+            "Ni" => material.ior = ctx.parse_f64(tokens.next(), "index of refraction")?,
+            "d" => material.dissolve = ctx.parse_f64(tokens.next(), "dissolve")?,
+            "Tr" => material.dissolve = 1.0 - ctx.parse_f64(tokens.next(), "transparency")?,
+            "illum" => {
+                let token = tokens.next().unwrap_or("");
+                material.illum = token
+                    .parse()
+                    .map_err(|_| ctx.malformed(format!("invalid illum `{}`", token)))?;
+            }
+            // Texture maps and other statements are not supported yet.
+            _ => {}
+        }
+    }
+
+    if let Some((name, material)) = current {
+        materials.insert(name, material);
+    }
+    Ok(materials)
+}
+
+/// Loads an OBJ file, and any material libraries it references, as one mesh
+/// per material.
+pub fn load_obj<P: AsRef<Path>>(path: P) -> Result<Vec<Box<dyn Hittable + Send + Sync>>, ObjError> {
+    let path = path.as_ref();
+    let directory = path.parent().unwrap_or_else(|| Path::new(""));
+
+    let mut positions: Vec<Point3> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut uvs: Vec<(f64, f64)> = Vec::new();
+    let mut materials: HashMap<String, MtlMaterial> = HashMap::new();
+    let mut groups = vec![FaceGroup {
+        material: None,
+        faces: Vec::new(),
+    }];
+
+    for (line, text) in lines(path)? {
+        let ctx = Context { path, line };
+        let mut tokens = text.split_whitespace();
+        match tokens.next() {
+            Some("v") => positions.push(ctx.parse_vec3(&mut tokens, "vertex coordinate")?),
+            Some("vn") => normals.push(ctx.parse_vec3(&mut tokens, "normal coordinate")?),
+            Some("vt") => {
+                let u = ctx.parse_f64(tokens.next(), "texture coordinate")?;
+                let v = match tokens.next() {
+                    Some(token) => ctx.parse_f64(Some(token), "texture coordinate")?,
+                    None => 0.0,
+                };
+                uvs.push((u, v));
+            }
+            Some("f") => {
+                let corners = tokens
+                    .map(|corner| {
+                        parse_face_vertex(&ctx, corner, positions.len(), uvs.len(), normals.len())
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                if corners.len() < 3 {
+                    return Err(ctx.malformed("face needs at least three vertices"));
+                }
+                let group = groups.last_mut().expect("there is always a face group");
+                for i in 1..corners.len() - 1 {
+                    group.faces.push([corners[0], corners[i], corners[i + 1]]);
+                }
+            }
+            Some("mtllib") => {
+                // Libraries are separated by spaces, unless the whole rest of
+                // the line names one library with spaces in its file name.
+                let rest = text.trim_start()["mtllib".len()..].trim();
+                let libraries: Vec<&str> =
+                    if rest.contains(char::is_whitespace) && directory.join(rest).is_file() {
+                        vec![rest]
+                    } else {
+                        tokens.collect()
+                    };
+                for library in libraries {
+                    materials.extend(load_mtl(directory.join(library))?);
+                }
+            }
+            Some("usemtl") => {
+                let name = tokens.collect::<Vec<_>>().join(" ");
+                if !materials.contains_key(&name) {
+                    return Err(ObjError::UnknownMaterial {
+                        path: path.to_path_buf(),
+                        line,
+                        name,
+                    });
+                }
+                groups.push(FaceGroup {
+                    material: Some(name),
+                    faces: Vec::new(),
+                });
+            }
+            // Object and group names, smoothing groups and lines are ignored.
+            _ => {}
+        }
+    }
+
+    let default_material = MtlMaterial::default();
+    Ok(groups
+        .into_iter()
+        .filter(|group| !group.faces.is_empty())
+        .map(|group| {
+            let material = group
+                .material
+                .as_ref()
+                .map_or(&default_material, |name| &materials[name]);
+            build_group(&group, material, &positions, &normals, &uvs)
+        })
+        .collect())
+}
+
+fn parse_face_vertex(
+    ctx: &Context,
+    corner: &str,
+    position_count: usize,
+    uv_count: usize,
+    normal_count: usize,
+) -> Result<FaceVertex, ObjError> {
+    let mut parts = corner.split('/');
+    let position = ctx.resolve_index(parts.next().unwrap_or(""), position_count)?;
+    let uv = match parts.next() {
+        Some("") | None => None,
+        Some(token) => Some(ctx.resolve_index(token, uv_count)?),
+    };
+    let normal = match parts.next() {
+        Some("") | None => None,
+        Some(token) => Some(ctx.resolve_index(token, normal_count)?),
+    };
+    if parts.next().is_some() {
+        return Err(ctx.malformed(format!("invalid face vertex `{}`", corner)));
+    }
+    Ok((position, uv, normal))
+}
+
+/// Re-indexes a face group so every unique corner becomes one mesh vertex.
+fn build_group(
+    group: &FaceGroup,
+    material: &MtlMaterial,
+    positions: &[Point3],
+    normals: &[Vec3],
+    uvs: &[(f64, f64)],
+) -> Box<dyn Hittable + Send + Sync> {
+    let mut vertex_ids: HashMap<FaceVertex, usize> = HashMap::new();
+    let mut corners: Vec<FaceVertex> = Vec::new();
+    let indices = group
+        .faces
+        .iter()
+        .map(|face| {
+            let mut triangle = [0; 3];
+            for (slot, corner) in triangle.iter_mut().zip(face.iter()) {
+                *slot = *vertex_ids.entry(*corner).or_insert_with(|| {
+                    corners.push(*corner);
+                    corners.len() - 1
+                });
+            }
+            triangle
+        })
+        .collect();
+
+    // Normals and uvs are only used when every corner in the group has one.
+    let mesh_positions = corners.iter().map(|&(p, _, _)| positions[p]).collect();
+    let mesh_normals = corners
+        .iter()
+        .map(|&(_, _, n)| n.map(|n| normals[n]))
+        .collect::<Option<Vec<_>>>()
+        .unwrap_or_default();
+    let mesh_uvs = corners
+        .iter()
+        .map(|&(_, t, _)| t.map(|t| uvs[t]))
+        .collect::<Option<Vec<_>>>()
+        .unwrap_or_default();
+
+    material.build_mesh(mesh_positions, mesh_normals, mesh_uvs, indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ray::Ray;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::fs;
+
+    /// Writes `files` into a fresh directory under the system temp dir, which
+    /// the test removes once it has loaded them.
+    fn write_files(test_name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("rtiow-obj-{}-{}", std::process::id(), test_name));
+        fs::create_dir_all(&directory).unwrap();
+        for (name, contents) in files {
+            fs::write(directory.join(name), contents).unwrap();
+        }
+        directory
+    }
+
+    const QUAD: &str = "\
+# unit square in the z=0 plane
+mtllib quad.mtl
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+usemtl shiny
+f 1/1/1 2/2/1 3/3/1 4/4/1
+";
+
+    const QUAD_MTL: &str = "\
+newmtl shiny
+Kd 0.1 0.2 0.3
+Ks 0.9 0.9 0.9
+Ns 98
+illum 3
+
+newmtl glass
+Ni 1.33
+d 0.5
+";
+
+    #[test]
+    fn test_load_obj() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let directory = write_files("load", &[("quad.obj", QUAD), ("quad.mtl", QUAD_MTL)]);
+        let meshes = load_obj(directory.join("quad.obj"));
+        fs::remove_dir_all(&directory).unwrap();
+        let meshes = meshes.unwrap();
+        assert_eq!(1, meshes.len());
+
+        let ray = Ray::new(Point3::new(0.75, 0.25, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let record = meshes.hit(&ray, 0.001, f64::INFINITY, &mut rng).unwrap();
+        assert!(approx_eq!(f64, record.t, 1.0, ulps = 2));
+        assert!(approx_eq!(f64, record.u, 0.75, epsilon = 1e-12));
+        assert!(approx_eq!(f64, record.v, 0.25, epsilon = 1e-12));
+        assert_eq!(Vec3::new(0.0, 0.0, 1.0), record.normal);
+    }
+
+    #[test]
+    fn test_material_library_with_spaces() {
+        let obj = QUAD.replace("mtllib quad.mtl", "mtllib my  quad.mtl");
+        let directory = write_files(
+            "mtl-spaces",
+            &[("quad.obj", &obj), ("my  quad.mtl", QUAD_MTL)],
+        );
+        let meshes = load_obj(directory.join("quad.obj"));
+        fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(1, meshes.unwrap().len());
+    }
+
+    #[test]
+    fn test_load_mtl() {
+        let directory = write_files("mtl", &[("quad.mtl", QUAD_MTL)]);
+        let materials = load_mtl(directory.join("quad.mtl"));
+        fs::remove_dir_all(&directory).unwrap();
+        let materials = materials.unwrap();
+        assert_eq!(
+            MtlMaterial {
+                diffuse: Color::new(0.1, 0.2, 0.3),
+                specular: Color::new(0.9, 0.9, 0.9),
+                shininess: 98.0,
+                ior: 1.5,
+                dissolve: 1.0,
+                illum: 3,
+            },
+            materials["shiny"]
+        );
+        assert!(approx_eq!(f64, materials["glass"].ior, 1.33, ulps = 2));
+        assert!(approx_eq!(f64, materials["glass"].dissolve, 0.5, ulps = 2));
+    }
+
+    #[test]
+    fn test_negative_indices() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let obj = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf -3 -2 -1\n";
+        let directory = write_files("negative", &[("tri.obj", obj)]);
+        let meshes = load_obj(directory.join("tri.obj"));
+        fs::remove_dir_all(&directory).unwrap();
+        let meshes = meshes.unwrap();
+        let ray = Ray::new(Point3::new(0.25, 0.25, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(meshes.hit(&ray, 0.001, f64::INFINITY, &mut rng).is_some());
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let result = load_obj("does/not/exist.obj");
+        assert!(matches!(result, Err(ObjError::Io { .. })));
+    }
+
+    #[test]
+    fn test_missing_material_library() {
+        let directory = write_files("missing-mtl", &[("quad.obj", QUAD)]);
+        let result = load_obj(directory.join("quad.obj"));
+        fs::remove_dir_all(&directory).unwrap();
+        match result {
+            Err(ObjError::Io { path, .. }) => assert!(path.ends_with("quad.mtl")),
+            _ => panic!("expected an Io error"),
+        }
+    }
+
+    #[test]
+    fn test_malformed_face() {
+        let obj = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2\n";
+        let directory = write_files("malformed", &[("tri.obj", obj)]);
+        let result = load_obj(directory.join("tri.obj"));
+        fs::remove_dir_all(&directory).unwrap();
+        match result {
+            Err(ObjError::Malformed { line, .. }) => assert_eq!(4, line),
+            _ => panic!("expected a Malformed error"),
+        }
+    }
+
+    #[test]
+    fn test_bad_index() {
+        let obj = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n";
+        let directory = write_files("bad-index", &[("tri.obj", obj)]);
+        let result = load_obj(directory.join("tri.obj"));
+        fs::remove_dir_all(&directory).unwrap();
+        match result {
+            Err(ObjError::BadIndex { line, index, .. }) => {
+                assert_eq!(4, line);
+                assert_eq!(4, index);
+            }
+            _ => panic!("expected a BadIndex error"),
+        }
+    }
+
+    #[test]
+    fn test_unknown_material() {
+        let obj = "v 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl missing\nf 1 2 3\n";
+        let directory = write_files("unknown-material", &[("tri.obj", obj)]);
+        let result = load_obj(directory.join("tri.obj"));
+        fs::remove_dir_all(&directory).unwrap();
+        match result {
+            Err(ObjError::UnknownMaterial { line, name, .. }) => {
+                assert_eq!(4, line);
+                assert_eq!("missing", name);
+            }
+            _ => panic!("expected an UnknownMaterial error"),
+        }
+    }
+}
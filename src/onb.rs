@@ -0,0 +1,63 @@
+use crate::vec3::Vec3;
+
+/// An orthonormal basis whose `w` axis points along a given direction, used to
+/// move between world space and a surface's local frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Onb {
+    pub u: Vec3,
+    pub v: Vec3,
+    pub w: Vec3,
+}
+
+impl Onb {
+    /// Builds a basis around `n`, which need not be normalized.
+    pub fn new(n: Vec3) -> Self {
+        let w = n.unit_vector();
+        // Any vector not parallel to w will do to start the cross products.
+        let a = if w.x.abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let v = w.cross(a).unit_vector();
+        let u = v.cross(w);
+        Onb { u, v, w }
+    }
+
+    /// Converts local coordinates to a world space vector.
+    pub fn local(&self, a: Vec3) -> Vec3 {
+        a.x * self.u + a.y * self.v + a.z * self.w
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_basis_is_orthonormal() {
+        for n in [
+            Vec3::new(0.0, 0.0, 2.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(-0.3, 0.8, 0.5),
+        ] {
+            let onb = Onb::new(n);
+            assert!(approx_eq!(
+                f64,
+                onb.w.dot(n.unit_vector()),
+                1.0,
+                epsilon = 1e-12
+            ));
+            for (a, b) in [(onb.u, onb.v), (onb.v, onb.w), (onb.w, onb.u)] {
+                assert!(approx_eq!(f64, a.dot(b), 0.0, epsilon = 1e-12));
+                assert!(approx_eq!(f64, a.length(), 1.0, epsilon = 1e-12));
+            }
+            assert!(approx_eq!(
+                f64,
+                onb.u.cross(onb.v).dot(onb.w),
+                1.0,
+                epsilon = 1e-12
+            ));
+        }
+    }
+}
@@ -0,0 +1,322 @@
+//! Turning rendered pixels into image files.
+
+use crate::aov::Aov;
+use crate::render::Bounds;
+use crate::tone_map::DisplayTransform;
+use crate::vec3::Color;
+use exr::prelude::{AnyChannel, AnyChannels, Encoding, FlatSamples, Image, Layer, LayerAttributes};
+use exr::prelude::{SmallVec, WritableImage};
+use image::codecs::hdr::HdrEncoder;
+use image::{ImageBuffer, Rgb, RgbImage};
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// File formats a render can be written in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// 8 bits per channel after the display transform: PNG, unless the file
+    /// extension names another format the `image` crate can write.
+    Png,
+    /// OpenEXR with 32-bit float channels, which can hold several layers.
+    Exr,
+    /// Radiance RGBE.
+    Hdr,
+    /// Portable float map.
+    Pfm,
+}
+
+impl Format {
+    /// The format implied by the extension of `path`. Anything that is not
+    /// a high dynamic range format is written with 8 bits per channel.
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("exr") => Format::Exr,
+            Some("hdr") => Format::Hdr,
+            Some("pfm") => Format::Pfm,
+            _ => Format::Png,
+        }
+    }
+}
+
+/// Writes the averaged, linear `pixels` to `path` in `format`, along with
+/// `aovs`. An EXR file holds the AOVs as layers, in channels named e.g.
+/// `albedo.R`; other formats write each to a separate file named by
+/// `aov_path`. `display` only applies to the image in 8-bit formats: the
+/// others keep the linear radiance, and 8-bit AOVs are mapped by
+/// `Aov::display_buffer` and quantized as they are, with no sRGB encoding.
+pub fn write_image(
+    path: &Path,
+    format: Format,
+    bounds: Bounds,
+    pixels: &[Color],
+    aovs: &[(Aov, Vec<Color>)],
+    display: DisplayTransform,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if format == Format::Exr {
+        let mut layers = vec![("", pixels)];
+        layers.extend(aovs.iter().map(|(aov, buffer)| (aov.name(), &buffer[..])));
+        write_exr(path, bounds, &layers)?;
+        return Ok(());
+    }
+
+    write_single(path, format, bounds, pixels, display)?;
+    for (aov, buffer) in aovs {
+        let path = aov_path(path, *aov);
+        if format == Format::Png {
+            save_rgb8(&path, quantize(&aov.display_buffer(buffer), bounds))?;
+        } else {
+            write_single(&path, format, bounds, buffer, display)?;
+        }
+    }
+    Ok(())
+}
+
+/// Where an AOV is written next to the image at `path`: `image.png` has its
+/// albedo in `image.albedo.png`.
+pub fn aov_path(path: &Path, aov: Aov) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(extension) => format!("{}.{}.{}", stem, aov.name(), extension.to_string_lossy()),
+        None => format!("{}.{}", stem, aov.name()),
+    };
+    path.with_file_name(name)
+}
+
+/// Writes one image in any format but EXR.
+fn write_single(
+    path: &Path,
+    format: Format,
+    bounds: Bounds,
+    pixels: &[Color],
+    display: DisplayTransform,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    match format {
+        Format::Png => save_rgb8(path, to_rgb8(pixels, bounds, 1, display))?,
+        Format::Exr => write_exr(path, bounds, &[("", pixels)])?,
+        Format::Hdr => write_hdr(BufWriter::new(File::create(path)?), bounds, pixels)?,
+        Format::Pfm => write_pfm(BufWriter::new(File::create(path)?), bounds, pixels)?,
+    }
+    Ok(())
+}
+
+/// Saves an 8-bit image as PNG, unless the extension of `path` names another
+/// format the `image` crate can write.
+fn save_rgb8(path: &Path, image: RgbImage) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let format = image::ImageFormat::from_path(path).unwrap_or(image::ImageFormat::Png);
+    image.save_with_format(path, format)?;
+    Ok(())
+}
+
+/// Averages the summed samples of each pixel, passes them through `display`
+/// and quantizes to 8 bits per channel. Pixels are in `render_image` order:
+/// top row first.
+pub fn to_rgb8(
+    pixels: &[Color],
+    bounds: Bounds,
+    samples_per_pixel: usize,
+    display: DisplayTransform,
+) -> RgbImage {
+    let scale = 1.0 / samples_per_pixel as f64;
+    let shown: Vec<_> = pixels
+        .iter()
+        .map(|&pixel| display.apply(scale * pixel))
+        .collect();
+    quantize(&shown, bounds)
+}
+
+/// Quantizes colors already in `[0, 1]` to 8 bits per channel.
+fn quantize(pixels: &[Color], bounds: Bounds) -> RgbImage {
+    let channel = |c: f64| (255.0 * c).round() as u8;
+    ImageBuffer::from_fn(bounds.width, bounds.height, |x, y| {
+        let pixel = pixels[(y * bounds.width + x) as usize];
+        Rgb([channel(pixel.x), channel(pixel.y), channel(pixel.z)])
+    })
+}
+
+/// Writes `layers` as a single-part EXR. The first layer's channels are the
+/// plain `R`, `G` and `B` that viewers show, whatever its name.
+pub fn write_exr(
+    path: &Path,
+    bounds: Bounds,
+    layers: &[(&str, &[Color])],
+) -> Result<(), exr::error::Error> {
+    let mut channels = Vec::new();
+    for (i, (name, pixels)) in layers.iter().enumerate() {
+        let prefix = if i == 0 {
+            String::new()
+        } else {
+            format!("{}.", name)
+        };
+        let channel = |suffix: &str, component: fn(&Color) -> f64| {
+            let samples = pixels.iter().map(|pixel| component(pixel) as f32).collect();
+            AnyChannel::new(
+                format!("{}{}", prefix, suffix).as_str(),
+                FlatSamples::F32(samples),
+            )
+        };
+        channels.push(channel("R", |c| c.x));
+        channels.push(channel("G", |c| c.y));
+        channels.push(channel("B", |c| c.z));
+    }
+    let layer = Layer::new(
+        (bounds.width as usize, bounds.height as usize),
+        LayerAttributes::default(),
+        Encoding::FAST_LOSSLESS,
+        AnyChannels::sort(SmallVec::from_vec(channels)),
+    );
+    Image::from_layer(layer).write().to_file(path)
+}
+
+/// Writes `pixels` as a Radiance HDR image.
+pub fn write_hdr(writer: impl Write, bounds: Bounds, pixels: &[Color]) -> image::ImageResult<()> {
+    let pixels: Vec<_> = pixels
+        .iter()
+        .map(|pixel| Rgb([pixel.x as f32, pixel.y as f32, pixel.z as f32]))
+        .collect();
+    HdrEncoder::new(writer).encode(&pixels, bounds.width as usize, bounds.height as usize)
+}
+
+/// Writes `pixels` as a little-endian color PFM, which stores the bottom
+/// row first.
+pub fn write_pfm(mut writer: impl Write, bounds: Bounds, pixels: &[Color]) -> io::Result<()> {
+    write!(writer, "PF\n{} {}\n-1.0\n", bounds.width, bounds.height)?;
+    for row in pixels.chunks(bounds.width as usize).rev() {
+        for pixel in row {
+            for &c in [pixel.x, pixel.y, pixel.z].iter() {
+                writer.write_all(&(c as f32).to_le_bytes())?;
+            }
+        }
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tone_map::ToneMap;
+
+    #[test]
+    fn test_to_rgb8() {
+        let bounds = Bounds {
+            width: 2,
+            height: 1,
+        };
+        // Two samples each: a quarter grey and an over-exposed red.
+        let pixels = [Color::new(0.5, 0.5, 0.5), Color::new(8.0, 0.0, -1.0)];
+        let image = to_rgb8(&pixels, bounds, 2, DisplayTransform::default());
+        assert_eq!(Rgb([137, 137, 137]), *image.get_pixel(0, 0));
+        assert_eq!(Rgb([255, 0, 0]), *image.get_pixel(1, 0));
+
+        let display = DisplayTransform {
+            exposure: -1.0,
+            tone_map: ToneMap::Reinhard,
+        };
+        let image = to_rgb8(&pixels, bounds, 2, display);
+        // 4 * 2^-1 = 2 maps to 2/3.
+        assert_eq!(Rgb([213, 0, 0]), *image.get_pixel(1, 0));
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(Format::Exr, Format::from_path(Path::new("out/render.EXR")));
+        assert_eq!(Format::Hdr, Format::from_path(Path::new("render.hdr")));
+        assert_eq!(Format::Pfm, Format::from_path(Path::new("render.pfm")));
+        assert_eq!(Format::Png, Format::from_path(Path::new("render.jpg")));
+        assert_eq!(Format::Png, Format::from_path(Path::new("render")));
+    }
+
+    #[test]
+    fn test_aov_path() {
+        assert_eq!(
+            PathBuf::from("out/render.albedo.png"),
+            aov_path(Path::new("out/render.png"), Aov::Albedo)
+        );
+        assert_eq!(
+            PathBuf::from("render.object_id"),
+            aov_path(Path::new("render"), Aov::ObjectId)
+        );
+    }
+
+    #[test]
+    fn test_write_normal_aov() {
+        let bounds = Bounds {
+            width: 1,
+            height: 1,
+        };
+        let directory = std::env::temp_dir().join(format!("rtiow-aov-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("image.png");
+        let normal = vec![Color::new(0.0, 0.0, 1.0)];
+        let result = write_image(
+            &path,
+            Format::Png,
+            bounds,
+            &[Color::default()],
+            &[(Aov::Normal, normal)],
+            DisplayTransform::default(),
+        )
+        .and_then(|()| Ok(image::open(aov_path(&path, Aov::Normal))?.to_rgb8()));
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        // 0.5 * (n + 1), quantized without a second pass through sRGB.
+        assert_eq!(Rgb([128, 128, 255]), *result.unwrap().get_pixel(0, 0));
+    }
+
+    #[test]
+    fn test_write_pfm() {
+        let bounds = Bounds {
+            width: 1,
+            height: 2,
+        };
+        let pixels = [Color::new(1.0, 2.0, 3.0), Color::new(0.5, 0.0, 40.0)];
+        let mut bytes = Vec::new();
+        write_pfm(&mut bytes, bounds, &pixels).unwrap();
+
+        let header = b"PF\n1 2\n-1.0\n";
+        assert_eq!(header, &bytes[..header.len()]);
+        let floats: Vec<f32> = bytes[header.len()..]
+            .chunks(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        assert_eq!(vec![0.5, 0.0, 40.0, 1.0, 2.0, 3.0], floats);
+    }
+
+    #[test]
+    fn test_write_exr_layers() {
+        use exr::prelude::{read_all_flat_layers_from_file, FlatSamples};
+
+        let bounds = Bounds {
+            width: 2,
+            height: 1,
+        };
+        let beauty = [Color::new(100.0, 0.5, 0.0), Color::new(0.0, 0.0, 1.0)];
+        let albedo = [Color::new(0.25, 0.5, 0.75), Color::new(1.0, 1.0, 1.0)];
+        let path = std::env::temp_dir().join(format!("layers-{}.exr", std::process::id()));
+        write_exr(&path, bounds, &[("beauty", &beauty), ("albedo", &albedo)]).unwrap();
+        let image = read_all_flat_layers_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let channel = |name: &str| {
+            let channel = image.layer_data[0]
+                .channel_data
+                .list
+                .iter()
+                .find(|channel| channel.name == *name)
+                .unwrap();
+            match &channel.sample_data {
+                FlatSamples::F32(samples) => samples.clone(),
+                _ => panic!("{} is not stored as f32", name),
+            }
+        };
+        // Values above one survive.
+        assert_eq!(vec![100.0, 0.0], channel("R"));
+        assert_eq!(vec![0.5, 1.0], channel("albedo.G"));
+        assert_eq!(6, image.layer_data[0].channel_data.list.len());
+    }
+}
@@ -0,0 +1,179 @@
+//! Probability densities over directions, used to importance sample scattered
+//! rays. Densities are per unit solid angle.
+
+use crate::hittable::Hittable;
+use crate::onb::Onb;
+use crate::vec3::{Point3, Vec3};
+use rand::{Rng, RngCore};
+use std::f64::consts::PI;
+
+pub trait Pdf {
+    /// Density of generating `direction`, which need not be normalized.
+    fn value(&self, direction: Vec3) -> f64;
+
+    /// A random direction distributed according to this density.
+    fn generate(&self, rng: &mut dyn RngCore) -> Vec3;
+}
+
+/// Uniform over all directions.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SpherePdf;
+
+impl Pdf for SpherePdf {
+    fn value(&self, _direction: Vec3) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+
+    fn generate(&self, rng: &mut dyn RngCore) -> Vec3 {
+        Vec3::random_unit_vector(rng)
+    }
+}
+
+/// Proportional to the cosine of the angle to a surface normal, matching a
+/// Lambertian surface.
+#[derive(Clone, Copy, Debug)]
+pub struct CosinePdf {
+    frame: Onb,
+}
+
+impl CosinePdf {
+    pub fn new(normal: Vec3) -> Self {
+        CosinePdf {
+            frame: Onb::new(normal),
+        }
+    }
+}
+
+impl Pdf for CosinePdf {
+    fn value(&self, direction: Vec3) -> f64 {
+        let cosine = direction.unit_vector().dot(self.frame.w);
+        (cosine / PI).max(0.0)
+    }
+
+    fn generate(&self, rng: &mut dyn RngCore) -> Vec3 {
+        let r1: f64 = rng.gen();
+        let r2: f64 = rng.gen();
+        let phi = 2.0 * PI * r1;
+        let local = Vec3::new(
+            phi.cos() * r2.sqrt(),
+            phi.sin() * r2.sqrt(),
+            (1.0 - r2).sqrt(),
+        );
+        self.frame.local(local)
+    }
+}
+
+/// Directions from `origin` towards a set of objects, typically the lights, as
+/// sampled by `Hittable::random`.
+pub struct HittablePdf<'a> {
+    objects: &'a dyn Hittable,
+    origin: Point3,
+}
+
+impl<'a> HittablePdf<'a> {
+    pub fn new(objects: &'a dyn Hittable, origin: Point3) -> Self {
+        HittablePdf { objects, origin }
+    }
+}
+
+impl Pdf for HittablePdf<'_> {
+    fn value(&self, direction: Vec3) -> f64 {
+        self.objects.pdf_value(self.origin, direction)
+    }
+
+    fn generate(&self, rng: &mut dyn RngCore) -> Vec3 {
+        self.objects.random(self.origin, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable::quad::Quad;
+    use crate::hittable::sphere::Sphere;
+    use crate::material::diffuse_light::DiffuseLight;
+    use crate::vec3::Color;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// Monte Carlo estimate of the integral of `pdf` over the sphere, which
+    /// should be 1.
+    fn integral(pdf: &dyn Pdf) -> f64 {
+        let mut rng = StdRng::seed_from_u64(0);
+        let samples = 100_000;
+        let total: f64 = (0..samples)
+            .map(|_| pdf.value(Vec3::random_unit_vector(&mut rng)) * 4.0 * PI)
+            .sum();
+        total / samples as f64
+    }
+
+    #[test]
+    fn test_densities_integrate_to_one() {
+        assert!(approx_eq!(f64, integral(&SpherePdf), 1.0, epsilon = 1e-12));
+        let cosine = CosinePdf::new(Vec3::new(0.3, -1.0, 0.2));
+        assert!((integral(&cosine) - 1.0).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_light_densities() {
+        let light = Color::new(4.0, 4.0, 4.0);
+        let lights: Vec<Box<dyn Hittable + Send + Sync>> = vec![
+            Box::new(Sphere::new(
+                Point3::new(0.0, 2.0, 0.0),
+                1.0,
+                DiffuseLight::new(light),
+            )),
+            Box::new(Quad::new(
+                Point3::new(-1.0, 2.0, -1.0),
+                Vec3::new(2.0, 0.0, 0.0),
+                Vec3::new(0.0, 0.0, 2.0),
+                DiffuseLight::new(light),
+            )),
+        ];
+        let origin = Point3::default();
+        let up = Vec3::new(0.0, 1.0, 0.0);
+
+        // The sphere subtends a cone with half-angle 30 degrees.
+        let cone = 2.0 * PI * (1.0 - 30.0f64.to_radians().cos());
+        let sphere = HittablePdf::new(lights[0].as_ref(), origin);
+        assert!(approx_eq!(
+            f64,
+            sphere.value(up),
+            1.0 / cone,
+            epsilon = 1e-9
+        ));
+
+        // Straight up, the quad is 2 away and faces the origin: 2^2 / area.
+        let quad = HittablePdf::new(lights[1].as_ref(), origin);
+        assert!(approx_eq!(f64, quad.value(up), 1.0, epsilon = 1e-9));
+        assert_eq!(0.0, quad.value(Vec3::new(1.0, 0.0, 0.0)));
+
+        let both = HittablePdf::new(&lights, origin);
+        assert!(approx_eq!(
+            f64,
+            both.value(up),
+            0.5 * (1.0 / cone + 1.0),
+            epsilon = 1e-9
+        ));
+
+        let mut rng = StdRng::seed_from_u64(1);
+        for pdf in [sphere, quad, both] {
+            for _ in 0..100 {
+                assert!(pdf.value(pdf.generate(&mut rng)) > 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cosine_samples_stay_in_hemisphere() {
+        let normal = Vec3::new(0.0, 0.0, -1.0);
+        let pdf = CosinePdf::new(normal);
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..1000 {
+            let direction = pdf.generate(&mut rng);
+            assert!(direction.dot(normal) >= 0.0);
+            assert!(approx_eq!(f64, direction.length(), 1.0, epsilon = 1e-9));
+            assert!(pdf.value(direction) >= 0.0);
+        }
+    }
+}
@@ -4,11 +4,22 @@ use crate::vec3::{Point3, Vec3};
 pub struct Ray {
     pub origin: Point3,
     pub direction: Vec3,
+    /// The moment the ray was cast, within the camera's shutter interval.
+    pub time: f64,
 }
 
 impl Ray {
+    /// A ray cast at time zero.
     pub fn new(origin: Point3, direction: Vec3) -> Self {
-        Ray { origin, direction }
+        Ray::with_time(origin, direction, 0.0)
+    }
+
+    pub fn with_time(origin: Point3, direction: Vec3, time: f64) -> Self {
+        Ray {
+            origin,
+            direction,
+            time,
+        }
     }
 
     pub fn at(&self, t: f64) -> Point3 {
@@ -25,6 +36,7 @@ mod tests {
         let ray = Ray::new(Point3::new(1.0, 2.0, 3.0), Vec3::new(4.0, 5.0, 6.0));
         assert_eq!(ray.origin, Point3::new(1.0, 2.0, 3.0));
         assert_eq!(ray.direction, Vec3::new(4.0, 5.0, 6.0));
+        assert_eq!(ray.time, 0.0);
     }
 
     #[test]
@@ -0,0 +1,351 @@
+use crate::aov::Aov;
+use crate::camera::Camera;
+use crate::hittable::Hittable;
+use crate::integrator::{Integrator, IntegratorKind};
+use crate::ray::Ray;
+use crate::vec3::Color;
+
+use indicatif::{ParallelProgressIterator, ProgressBar, ProgressStyle};
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+use std::error::Error;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounds {
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RenderParams {
+    /// Samples per pixel, or the most any pixel takes with adaptive sampling.
+    pub samples_per_pixel: usize,
+    pub depth: i32,
+    pub background: Background,
+    pub integrator: IntegratorKind,
+    /// Seeds every random choice made while rendering, so the same seed always
+    /// gives the same image.
+    pub seed: u64,
+    /// Stops sampling pixels early once they are clean enough.
+    pub adaptive: Option<AdaptiveSampling>,
+}
+
+/// Settings for adaptive sampling, which spends samples where the image is
+/// noisy rather than evenly.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AdaptiveSampling {
+    /// Samples every pixel takes before its error estimate is trusted.
+    pub min_samples: usize,
+    /// Estimated error below which a pixel stops: the standard error of its
+    /// mean luminance, after mapping luminance into `[0, 1)`.
+    pub threshold: f64,
+}
+
+impl AdaptiveSampling {
+    pub const DEFAULT_MIN_SAMPLES: usize = 16;
+}
+
+/// Running mean and variance of one pixel's samples, by Welford's algorithm.
+#[derive(Default)]
+struct ErrorEstimate {
+    count: usize,
+    mean: f64,
+    squared_deviations: f64,
+}
+
+impl ErrorEstimate {
+    fn add(&mut self, color: Color) {
+        let luminance = (0.2126 * color.x + 0.7152 * color.y + 0.0722 * color.z).max(0.0);
+        // Compressing keeps a few bright samples from making dim pixels look
+        // noisier than they appear.
+        let value = luminance / (1.0 + luminance);
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.squared_deviations += delta * (value - self.mean);
+    }
+
+    /// Standard error of the mean, or infinity before there are two samples.
+    fn standard_error(&self) -> f64 {
+        if self.count < 2 {
+            return f64::INFINITY;
+        }
+        let n = self.count as f64;
+        (self.squared_deviations / ((n - 1.0) * n)).sqrt()
+    }
+}
+
+/// What a ray sees when it escapes the scene without hitting anything.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Background {
+    /// The book's white-to-blue sky gradient, which also lights the scene.
+    Sky,
+    /// A constant color. Use black for scenes lit only by emissive objects.
+    Solid(Color),
+}
+
+impl Background {
+    pub fn color(&self, ray: &Ray) -> Color {
+        match self {
+            Background::Sky => {
+                let unit_direction = ray.direction.unit_vector();
+                let t = 0.5 * (unit_direction.y + 1.0);
+                (1.0 - t) * Color::new(1.0, 1.0, 1.0) + t * Color::new(0.5, 0.7, 1.0)
+            }
+            Background::Solid(color) => *color,
+        }
+    }
+}
+
+/// Mixes `value` into `state` with the SplitMix64 finalizer. Unlike std's
+/// hashers, it is fixed, so anything derived from it is the same on every
+/// toolchain and platform.
+pub(crate) fn mix(state: u64, value: u64) -> u64 {
+    let mut z = (state ^ value).wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// The random number generator for one sample of one pixel. Each sample gets
+/// its own stream, so the image does not depend on how pixels are shared out
+/// between threads. The generator is a named one, rather than `SmallRng`, whose
+/// algorithm differs between platforms.
+pub fn sample_rng(seed: u64, pixel: u64, sample: u64) -> Pcg64Mcg {
+    Pcg64Mcg::seed_from_u64(mix(mix(seed, pixel), sample))
+}
+
+/// A rendered image, with pixels in order from the top row down.
+pub struct Frame {
+    /// Sum of the samples of each pixel.
+    pub pixels: Vec<Color>,
+    /// Number of samples each pixel took.
+    pub samples: Vec<usize>,
+    /// One buffer for each AOV asked for, already averaged.
+    pub aovs: Vec<(Aov, Vec<Color>)>,
+}
+
+impl Frame {
+    /// Averages the summed samples of each pixel into linear radiance.
+    pub fn average(&self) -> Vec<Color> {
+        self.pixels
+            .iter()
+            .zip(self.samples.iter())
+            .map(|(&pixel, &samples)| pixel / samples as f64)
+            .collect()
+    }
+
+    /// The buffer for `aov`, if it was rendered.
+    pub fn aov(&self, aov: Aov) -> Option<&[Color]> {
+        self.aovs
+            .iter()
+            .find(|(rendered, _)| *rendered == aov)
+            .map(|(_, buffer)| &buffer[..])
+    }
+}
+
+/// Renders the image, along with the `aovs` for the first surface each camera
+/// ray hits. AOVs cost an extra intersection test per sample and do not change
+/// the image.
+///
+/// With `params.adaptive`, each pixel keeps taking samples until its error
+/// estimate drops below the threshold or it reaches `samples_per_pixel`.
+pub fn render_image(
+    bounds: Bounds,
+    world: &(dyn Hittable + Sync),
+    lights: &[Box<dyn Hittable + Send + Sync>],
+    camera: Camera,
+    integrator: &(dyn Integrator + Sync),
+    params: RenderParams,
+    aovs: &[Aov],
+) -> Result<Frame, Box<dyn Error + Sync + Send>> {
+    let bar = ProgressBar::new(bounds.height as u64).with_style(
+        ProgressStyle::default_bar()
+            .template("Rendering: [{eta_precise}] {bar:40.cyan/blue} {pos:>7}/{len:} scanlines"),
+    );
+
+    let max_samples = params.samples_per_pixel;
+    let min_samples = params.adaptive.map_or(max_samples, |adaptive| {
+        adaptive.min_samples.min(max_samples)
+    });
+
+    let samples: Vec<(Color, usize, Vec<Color>)> = (0..bounds.height)
+        .into_par_iter()
+        .rev()
+        .progress_with(bar)
+        .flat_map(|y| {
+            (0..bounds.width).into_par_iter().map(move |x| {
+                let pixel = y as u64 * bounds.width as u64 + x as u64;
+                let mut color = Color::default();
+                let mut values = vec![Color::default(); aovs.len()];
+                let mut estimate = ErrorEstimate::default();
+                let mut taken = 0;
+                while taken < max_samples {
+                    let sample = taken;
+                    taken += 1;
+                    let mut rng = sample_rng(params.seed, pixel, sample as u64);
+                    let u = (x as f64 + rng.gen_range(0.0, 1.0)) / (bounds.width - 1) as f64;
+                    let v = (y as f64 + rng.gen_range(0.0, 1.0)) / (bounds.height - 1) as f64;
+
+                    let ray = camera.get_ray(u, v, &mut rng);
+                    let sample_color =
+                        integrator.ray_color(&ray, world, lights, params.background, &mut rng);
+                    color += sample_color;
+
+                    if !aovs.is_empty() {
+                        let record = world.hit(&ray, 0.001, f64::INFINITY, &mut rng);
+                        for (value, aov) in values.iter_mut().zip(aovs) {
+                            if aov.is_filtered() {
+                                *value += aov.value(&ray, record.as_ref());
+                            } else if sample == 0 {
+                                *value = aov.value(&ray, record.as_ref());
+                            }
+                        }
+                    }
+
+                    if let Some(adaptive) = params.adaptive {
+                        estimate.add(sample_color);
+                        if taken >= min_samples && estimate.standard_error() < adaptive.threshold {
+                            break;
+                        }
+                    }
+                }
+                for (value, aov) in values.iter_mut().zip(aovs) {
+                    if *aov == Aov::SampleCount {
+                        *value = Color::new(taken as f64, taken as f64, taken as f64);
+                    } else if aov.is_filtered() {
+                        *value /= taken as f64;
+                    }
+                }
+                (color, taken, values)
+            })
+        })
+        .collect();
+
+    let buffers = aovs
+        .iter()
+        .enumerate()
+        .map(|(i, &aov)| {
+            let buffer = samples.iter().map(|(_, _, values)| values[i]).collect();
+            (aov, buffer)
+        })
+        .collect();
+    Ok(Frame {
+        pixels: samples.iter().map(|(color, _, _)| *color).collect(),
+        samples: samples.iter().map(|(_, taken, _)| *taken).collect(),
+        aovs: buffers,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{test_world, test_world_camera};
+
+    fn render_with_threads(threads: usize, seed: u64, aovs: &[Aov]) -> Frame {
+        render_adaptive(threads, seed, None, aovs)
+    }
+
+    fn render_adaptive(
+        threads: usize,
+        seed: u64,
+        adaptive: Option<AdaptiveSampling>,
+        aovs: &[Aov],
+    ) -> Frame {
+        let bounds = Bounds {
+            width: 24,
+            height: 16,
+        };
+        let params = RenderParams {
+            samples_per_pixel: 32,
+            depth: 8,
+            background: Background::Sky,
+            integrator: IntegratorKind::Path,
+            seed,
+            adaptive,
+        };
+        let world = test_world();
+        let integrator = params.integrator.build(params.depth);
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap()
+            .install(|| {
+                render_image(
+                    bounds,
+                    &world,
+                    &[],
+                    test_world_camera(1.5),
+                    integrator.as_ref(),
+                    params,
+                    aovs,
+                )
+                .unwrap()
+            })
+    }
+
+    #[test]
+    fn test_sample_rng_is_fixed() {
+        // Pinned, so that renders match across platforms and releases.
+        assert_eq!(14969889998851498152, sample_rng(1, 2, 3).gen::<u64>());
+    }
+
+    #[test]
+    fn test_render_is_deterministic() {
+        let single = render_with_threads(1, 7, &[]).pixels;
+        assert_eq!(single, render_with_threads(4, 7, &[]).pixels);
+        assert_ne!(single, render_with_threads(4, 8, &[]).pixels);
+    }
+
+    #[test]
+    fn test_aovs() {
+        let frame = render_with_threads(2, 7, &Aov::ALL);
+        assert_eq!(render_with_threads(2, 7, &[]).pixels, frame.pixels);
+        assert_eq!(Aov::ALL.len(), frame.aovs.len());
+        for (aov, buffer) in frame.aovs.iter() {
+            assert_eq!(24 * 16, buffer.len(), "{:?}", aov);
+        }
+
+        // The camera looks down, so every ray hits something, and the bottom
+        // row sees the ground, whose normal points up. The objects have no
+        // IDs.
+        let (_, depth) = &frame.aovs[2];
+        assert!(depth.iter().all(|d| d.x > 0.0));
+        let (_, object_ids) = &frame.aovs[4];
+        assert!(object_ids.iter().all(|&id| id == Color::default()));
+        let (_, normal) = &frame.aovs[1];
+        let bottom = normal[24 * 15 + 12];
+        assert!(bottom.y > 0.9, "{:?}", bottom);
+    }
+
+    #[test]
+    fn test_adaptive_sampling() {
+        let adaptive = |threshold: f64| {
+            Some(AdaptiveSampling {
+                min_samples: 4,
+                threshold,
+            })
+        };
+
+        // A loose threshold stops every pixel at the minimum, and a threshold
+        // of zero never stops one early.
+        let frame = render_adaptive(2, 7, adaptive(1.0), &[]);
+        assert!(frame.samples.iter().all(|&n| n == 4));
+        let frame = render_adaptive(2, 7, adaptive(0.0), &[]);
+        assert_eq!(render_with_threads(2, 7, &[]).pixels, frame.pixels);
+
+        // In between, noisy pixels take more samples than clean ones, the
+        // same on any number of threads.
+        let frame = render_adaptive(1, 7, adaptive(0.02), &[Aov::SampleCount]);
+        assert!(frame.samples.iter().all(|&n| (4..=32).contains(&n)));
+        assert!(frame.samples.contains(&4) && frame.samples.contains(&32));
+        let counts = frame.aov(Aov::SampleCount).unwrap();
+        for (count, &n) in counts.iter().zip(frame.samples.iter()) {
+            assert_eq!(n as f64, count.x);
+        }
+        let threaded = render_adaptive(4, 7, adaptive(0.02), &[]);
+        assert_eq!(frame.pixels, threaded.pixels);
+        assert_eq!(frame.samples, threaded.samples);
+    }
+}
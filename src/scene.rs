@@ -0,0 +1,1588 @@
+//! Declarative scene files.
+//!
+//! A scene is a TOML document with a `[render]` table, a `[camera]` table,
+//! named `[textures.<name>]` and `[materials.<name>]` tables and an
+//! `[[objects]]` array. The `*Description` types below give each table's
+//! fields. Files it loads, such as meshes and images, are found relative to
+//! it, and it renders the same image every time, on any number of threads.
+//!
+//! ```toml
+//! [render]
+//! width = 400
+//! aspect_ratio = 1.7778
+//! samples_per_pixel = 100
+//! depth = 50
+//!
+//! [camera]
+//! look_from = [13.0, 2.0, 3.0]
+//! look_at = [0.0, 0.0, 0.0]
+//! vertical_fov = 20.0
+//! aperture = 0.1
+//! focus_distance = 10.0
+//!
+//! [materials.ground]
+//! type = "lambertian"
+//! albedo = [0.5, 0.5, 0.5]
+//!
+//! [[objects]]
+//! type = "sphere"
+//! center = [0.0, -1000.0, 0.0]
+//! radius = 1000.0
+//! material = "ground"
+//! ```
+
+use crate::aov::Aov;
+use crate::camera::Camera;
+use crate::hittable::constant_medium::ConstantMedium;
+use crate::hittable::cuboid::Cuboid;
+use crate::hittable::disk::Disk;
+use crate::hittable::instance::Instance;
+use crate::hittable::moving_sphere::MovingSphere;
+use crate::hittable::plane::Plane;
+use crate::hittable::quad::Quad;
+use crate::hittable::sphere::Sphere;
+use crate::hittable::tagged::TaggedHittable;
+use crate::hittable::triangle::Triangle;
+use crate::hittable::Hittable;
+use crate::integrator::IntegratorKind;
+use crate::material::dielectric::Dielectric;
+use crate::material::diffuse_light::DiffuseLight;
+use crate::material::isotropic::Isotropic;
+use crate::material::lambertian::Lambertian;
+use crate::material::metal::Metal;
+use crate::material::tagged::TaggedMaterial;
+use crate::material::Material;
+use crate::obj::{load_obj, ObjError};
+use crate::render::{AdaptiveSampling, Background, Bounds, RenderParams};
+use crate::texture::checker::CheckerTexture;
+use crate::texture::image_texture::{Filter, ImageTexture, WrapMode};
+use crate::texture::noise::{CloudTexture, MarbleTexture, NoiseTexture, WoodTexture};
+use crate::texture::perlin::Perlin;
+use crate::texture::solid_color::SolidColor;
+use crate::texture::Texture;
+use crate::tone_map::{DisplayTransform, ToneMap};
+use crate::transform::Transform;
+use crate::vec3::{Point3, Vec3};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Everything needed to render a scene file.
+pub struct Scene {
+    pub bounds: Bounds,
+    pub params: RenderParams,
+    pub display: DisplayTransform,
+    /// AOVs to write alongside the image.
+    pub aovs: Vec<Aov>,
+    /// Whether to denoise the image before writing it.
+    pub denoise: bool,
+    pub camera: Camera,
+    pub world: Vec<Box<dyn Hittable + Send + Sync>>,
+    /// Emissive objects to sample directly; each is also in `world`.
+    pub lights: Vec<Box<dyn Hittable + Send + Sync>>,
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    /// The scene file could not be read.
+    Io { path: PathBuf, source: io::Error },
+    /// The file is not valid TOML or does not match the scene schema.
+    Parse(toml::de::Error),
+    /// An entry parsed, but its values make no sense, e.g. an unknown material.
+    Invalid { entry: String, message: String },
+    /// A mesh object's OBJ file failed to load.
+    Mesh { entry: String, source: ObjError },
+    /// An image texture's file failed to load.
+    Image {
+        entry: String,
+        source: image::ImageError,
+    },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            SceneError::Parse(source) => write!(f, "invalid scene file: {}", source),
+            SceneError::Invalid { entry, message } => write!(f, "{}: {}", entry, message),
+            SceneError::Mesh { entry, source } => write!(f, "{}: {}", entry, source),
+            SceneError::Image { entry, source } => write!(f, "{}: {}", entry, source),
+        }
+    }
+}
+
+impl Error for SceneError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SceneError::Io { source, .. } => Some(source),
+            SceneError::Parse(source) => Some(source),
+            SceneError::Mesh { source, .. } => Some(source),
+            SceneError::Image { source, .. } => Some(source),
+            SceneError::Invalid { .. } => None,
+        }
+    }
+}
+
+fn invalid(entry: &str, message: impl Into<String>) -> SceneError {
+    SceneError::Invalid {
+        entry: entry.to_string(),
+        message: message.into(),
+    }
+}
+
+/// Returns an `Invalid` error for `entry` unless `condition` holds.
+fn ensure(condition: bool, entry: &str, message: &str) -> Result<(), SceneError> {
+    if condition {
+        Ok(())
+    } else {
+        Err(invalid(entry, message))
+    }
+}
+
+fn is_color(color: [f64; 3]) -> bool {
+    color.iter().all(|c| c.is_finite() && *c >= 0.0)
+}
+
+/// Loads a scene file. Relative mesh paths are resolved against its directory.
+pub fn load_scene<P: AsRef<Path>>(path: P) -> Result<Scene, SceneError> {
+    let path = path.as_ref();
+    let source = fs::read_to_string(path).map_err(|source| SceneError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    parse_scene(&source, path.parent().unwrap_or_else(|| Path::new("")))
+}
+
+/// Parses a scene from TOML source, resolving mesh paths against `base_dir`.
+pub fn parse_scene(source: &str, base_dir: &Path) -> Result<Scene, SceneError> {
+    let description: SceneDescription = toml::from_str(source).map_err(SceneError::Parse)?;
+    description.build(base_dir)
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneDescription {
+    render: RenderDescription,
+    camera: CameraDescription,
+    #[serde(default)]
+    textures: BTreeMap<String, TextureDescription>,
+    /// Numbered from 1 in alphabetical order for the material ID AOV.
+    /// Materials read from MTL files have ID 0.
+    #[serde(default)]
+    materials: BTreeMap<String, MaterialDescription>,
+    /// Numbered from 1 in the order they appear for the object ID AOV.
+    #[serde(default)]
+    objects: Vec<ObjectEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RenderDescription {
+    /// At least 2, as is the height it gives with `aspect_ratio`.
+    width: u32,
+    #[serde(default = "default_aspect_ratio")]
+    aspect_ratio: f64,
+    #[serde(default = "default_samples_per_pixel")]
+    samples_per_pixel: usize,
+    #[serde(default = "default_depth")]
+    depth: i32,
+    /// Solid background color; the sky gradient when absent.
+    background: Option<[f64; 3]>,
+    /// The path tracer when absent.
+    integrator: Option<IntegratorDescription>,
+    /// Change it for a different pattern of noise.
+    #[serde(default)]
+    seed: u64,
+    /// Stops to brighten 8-bit images by, or darken them if negative.
+    #[serde(default)]
+    exposure: f64,
+    /// How 8-bit images map bright values into range: `clamp` (the default),
+    /// `reinhard`, `aces` or `hable`.
+    #[serde(default)]
+    tone_map: ToneMap,
+    /// Extra per-pixel buffers to write with the image: any of `albedo`,
+    /// `normal`, `depth`, `position`, `object_id`, `material_id` and
+    /// `sample_count`.
+    #[serde(default)]
+    aovs: Vec<Aov>,
+    /// Filters the noise out of the image, for quick previews at low sample
+    /// counts.
+    #[serde(default)]
+    denoise: bool,
+    /// Every pixel takes `samples_per_pixel` samples when absent.
+    adaptive: Option<AdaptiveDescription>,
+}
+
+/// `adaptive = { threshold = t, min_samples = n }` stops sampling each pixel
+/// once the standard error of its luminance drops below `t`, after at least
+/// `n` samples (default 16) and at most `samples_per_pixel`. A `sample_count`
+/// AOV shows where the samples went.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AdaptiveDescription {
+    threshold: f64,
+    #[serde(default = "default_min_samples")]
+    min_samples: usize,
+}
+
+/// `integrator = { type = "..." }`: how rays are turned into colors.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum IntegratorDescription {
+    Path,
+    AmbientOcclusion { distance: f64 },
+    Whitted,
+    Normals,
+    Depth,
+    MaterialIds,
+}
+
+fn default_aspect_ratio() -> f64 {
+    16.0 / 9.0
+}
+
+fn default_samples_per_pixel() -> usize {
+    100
+}
+
+fn default_depth() -> i32 {
+    50
+}
+
+fn default_min_samples() -> usize {
+    AdaptiveSampling::DEFAULT_MIN_SAMPLES
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CameraDescription {
+    look_from: [f64; 3],
+    look_at: [f64; 3],
+    #[serde(default = "default_v_up")]
+    v_up: [f64; 3],
+    vertical_fov: f64,
+    #[serde(default)]
+    aperture: f64,
+    /// Defaults to the distance between `look_from` and `look_at`.
+    focus_distance: Option<f64>,
+    /// Shutter open and close times, over which moving spheres blur. Defaults
+    /// to an instantaneous shutter.
+    #[serde(default)]
+    shutter: [f64; 2],
+}
+
+fn default_v_up() -> [f64; 3] {
+    [0.0, 1.0, 0.0]
+}
+
+/// A color given inline, or the name of an entry in `[textures]`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TextureReference {
+    Color([f64; 3]),
+    Named(String),
+}
+
+/// A `[textures.<name>]` table. The `noise`, `marble`, `wood` and `cloud`
+/// textures are built from Perlin noise, with an optional `seed` (default 0) so
+/// the same file always renders the same surfaces.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum TextureDescription {
+    Solid {
+        color: [f64; 3],
+    },
+    Checker {
+        scale: f64,
+        even: TextureReference,
+        odd: TextureReference,
+    },
+    /// A PNG, JPEG or Radiance HDR file, e.g. an earth map for a sphere.
+    Image {
+        path: PathBuf,
+        /// `repeat` (the default) or `clamp`.
+        #[serde(default = "default_wrap_mode")]
+        wrap: WrapMode,
+        /// `bilinear` (the default) or `nearest`.
+        #[serde(default = "default_filter")]
+        filter: Filter,
+    },
+    Noise {
+        #[serde(default)]
+        seed: u64,
+        #[serde(default = "default_noise_scale")]
+        scale: f64,
+    },
+    Marble {
+        #[serde(default)]
+        seed: u64,
+        #[serde(default = "default_noise_scale")]
+        scale: f64,
+        base: [f64; 3],
+        vein: [f64; 3],
+    },
+    Wood {
+        #[serde(default)]
+        seed: u64,
+        #[serde(default = "default_noise_scale")]
+        scale: f64,
+        rings: f64,
+        light: [f64; 3],
+        dark: [f64; 3],
+    },
+    Cloud {
+        #[serde(default)]
+        seed: u64,
+        #[serde(default = "default_noise_scale")]
+        scale: f64,
+        #[serde(default = "default_octaves")]
+        octaves: usize,
+        sky: [f64; 3],
+        cloud: [f64; 3],
+    },
+}
+
+fn default_noise_scale() -> f64 {
+    1.0
+}
+
+fn default_octaves() -> usize {
+    6
+}
+
+fn default_wrap_mode() -> WrapMode {
+    WrapMode::Repeat
+}
+
+fn default_filter() -> Filter {
+    Filter::Bilinear
+}
+
+/// A `[materials.<name>]` table. Albedo and emission are a color or the name
+/// of a texture.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum MaterialDescription {
+    Lambertian {
+        albedo: TextureReference,
+    },
+    Metal {
+        albedo: TextureReference,
+        #[serde(default)]
+        fuzz: f64,
+    },
+    Dielectric {
+        index_of_refraction: f64,
+    },
+    DiffuseLight {
+        emit: TextureReference,
+    },
+    /// Scatters equally in every direction, for filling a `medium`.
+    Isotropic {
+        albedo: TextureReference,
+    },
+}
+
+/// An object, optionally placed with a list of transform steps applied in
+/// order.
+#[derive(Deserialize)]
+struct ObjectEntry {
+    #[serde(flatten)]
+    object: ObjectDescription,
+    #[serde(default)]
+    transform: Vec<TransformStep>,
+    medium: Option<MediumDescription>,
+}
+
+/// Fills an object with smoke or fog: a constant-density volume, usually with
+/// an `isotropic` material. The object's surface only bounds the volume; its
+/// own material is never seen.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MediumDescription {
+    density: f64,
+    material: String,
+}
+
+/// `{ translate = [x, y, z] }`, `{ scale = [x, y, z] }` or
+/// `{ rotate = { axis = [x, y, z], degrees = d } }`.
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum TransformStep {
+    Translate([f64; 3]),
+    Scale([f64; 3]),
+    Rotate { axis: [f64; 3], degrees: f64 },
+}
+
+/// An `[[objects]]` entry. Spheres and quads with a `diffuse_light` material,
+/// and neither a transform nor a medium, are also sampled directly as lights,
+/// which keeps small lights from rendering as scattered fireflies.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum ObjectDescription {
+    Sphere {
+        center: [f64; 3],
+        radius: f64,
+        material: String,
+    },
+    /// Travels from `center0` at `time0` to `center1` at `time1`.
+    MovingSphere {
+        center0: [f64; 3],
+        center1: [f64; 3],
+        #[serde(default)]
+        time0: f64,
+        #[serde(default = "default_time1")]
+        time1: f64,
+        radius: f64,
+        material: String,
+    },
+    Triangle {
+        vertices: [[f64; 3]; 3],
+        material: String,
+    },
+    /// Corner `q` and edges `u` and `v`.
+    Quad {
+        q: [f64; 3],
+        u: [f64; 3],
+        v: [f64; 3],
+        material: String,
+    },
+    Disk {
+        center: [f64; 3],
+        normal: [f64; 3],
+        radius: f64,
+        material: String,
+    },
+    /// Infinite.
+    Plane {
+        point: [f64; 3],
+        normal: [f64; 3],
+        material: String,
+    },
+    /// Axis-aligned.
+    #[serde(rename = "box")]
+    Cuboid {
+        min: [f64; 3],
+        max: [f64; 3],
+        material: String,
+    },
+    /// An OBJ file, with materials from its MTL libraries.
+    Mesh { path: PathBuf },
+}
+
+/// Composes an object's transform steps, or returns `None` if it has none.
+fn build_transform(steps: &[TransformStep], entry: &str) -> Result<Option<Transform>, SceneError> {
+    if steps.is_empty() {
+        return Ok(None);
+    }
+    let mut transform = Transform::identity();
+    for step in steps {
+        let next = match step {
+            TransformStep::Translate(offset) => Transform::translation((*offset).into()),
+            TransformStep::Scale(factors) => {
+                ensure(
+                    factors.iter().all(|f| f.is_finite() && *f != 0.0),
+                    entry,
+                    "scale factors must be non-zero",
+                )?;
+                Transform::scaling((*factors).into())
+            }
+            TransformStep::Rotate { axis, degrees } => {
+                ensure(
+                    Vec3::from(*axis).length_squared() > 0.0 && degrees.is_finite(),
+                    entry,
+                    "rotation needs a non-zero axis",
+                )?;
+                Transform::rotation((*axis).into(), *degrees)
+            }
+        };
+        transform = transform.then(next);
+    }
+    Ok(Some(transform))
+}
+
+fn default_time1() -> f64 {
+    1.0
+}
+
+type SharedMaterial = Arc<dyn Material + Send + Sync>;
+type SharedTexture = Arc<dyn Texture + Send + Sync>;
+
+/// Builds named textures on demand, so textures may refer to each other in any
+/// order.
+struct Textures<'a> {
+    base_dir: &'a Path,
+    descriptions: &'a BTreeMap<String, TextureDescription>,
+    built: HashMap<String, SharedTexture>,
+    in_progress: Vec<String>,
+}
+
+impl Textures<'_> {
+    fn resolve(
+        &mut self,
+        reference: &TextureReference,
+        entry: &str,
+        field: &str,
+    ) -> Result<SharedTexture, SceneError> {
+        match reference {
+            TextureReference::Color(color) => {
+                ensure(
+                    is_color(*color),
+                    entry,
+                    &format!("{} must not be negative", field),
+                )?;
+                Ok(Arc::new(SolidColor::new((*color).into())))
+            }
+            TextureReference::Named(name) => self.get(name, entry),
+        }
+    }
+
+    fn get(&mut self, name: &str, entry: &str) -> Result<SharedTexture, SceneError> {
+        if let Some(texture) = self.built.get(name) {
+            return Ok(Arc::clone(texture));
+        }
+
+        let description = self
+            .descriptions
+            .get(name)
+            .ok_or_else(|| invalid(entry, format!("unknown texture `{}`", name)))?;
+        let texture_entry = format!("textures.{}", name);
+        ensure(
+            !self.in_progress.iter().any(|visiting| visiting == name),
+            &texture_entry,
+            "texture refers to itself",
+        )?;
+
+        self.in_progress.push(name.to_string());
+        let texture = description.build(self, &texture_entry)?;
+        self.in_progress.pop();
+
+        self.built.insert(name.to_string(), Arc::clone(&texture));
+        Ok(texture)
+    }
+}
+
+fn perlin(seed: u64) -> Perlin {
+    Perlin::new(&mut StdRng::seed_from_u64(seed))
+}
+
+fn ensure_noise_scale(scale: f64, entry: &str) -> Result<(), SceneError> {
+    ensure(
+        scale.is_finite() && scale > 0.0,
+        entry,
+        "scale must be positive",
+    )
+}
+
+impl TextureDescription {
+    fn build(&self, textures: &mut Textures, entry: &str) -> Result<SharedTexture, SceneError> {
+        Ok(match self {
+            TextureDescription::Solid { color } => {
+                ensure(is_color(*color), entry, "color must not be negative")?;
+                Arc::new(SolidColor::new((*color).into()))
+            }
+            TextureDescription::Checker { scale, even, odd } => {
+                ensure(
+                    scale.is_finite() && *scale > 0.0,
+                    entry,
+                    "scale must be positive",
+                )?;
+                let even = textures.resolve(even, entry, "even")?;
+                let odd = textures.resolve(odd, entry, "odd")?;
+                Arc::new(CheckerTexture::new(*scale, even, odd))
+            }
+            TextureDescription::Image { path, wrap, filter } => {
+                let texture =
+                    ImageTexture::open(textures.base_dir.join(path)).map_err(|source| {
+                        SceneError::Image {
+                            entry: entry.to_string(),
+                            source,
+                        }
+                    })?;
+                Arc::new(texture.with_wrap_mode(*wrap).with_filter(*filter))
+            }
+            TextureDescription::Noise { seed, scale } => {
+                ensure_noise_scale(*scale, entry)?;
+                Arc::new(NoiseTexture::new(perlin(*seed), *scale))
+            }
+            TextureDescription::Marble {
+                seed,
+                scale,
+                base,
+                vein,
+            } => {
+                ensure_noise_scale(*scale, entry)?;
+                ensure(
+                    is_color(*base) && is_color(*vein),
+                    entry,
+                    "colors must not be negative",
+                )?;
+                Arc::new(MarbleTexture::new(
+                    perlin(*seed),
+                    *scale,
+                    (*base).into(),
+                    (*vein).into(),
+                ))
+            }
+            TextureDescription::Wood {
+                seed,
+                scale,
+                rings,
+                light,
+                dark,
+            } => {
+                ensure_noise_scale(*scale, entry)?;
+                ensure(
+                    rings.is_finite() && *rings > 0.0,
+                    entry,
+                    "rings must be positive",
+                )?;
+                ensure(
+                    is_color(*light) && is_color(*dark),
+                    entry,
+                    "colors must not be negative",
+                )?;
+                Arc::new(WoodTexture::new(
+                    perlin(*seed),
+                    *scale,
+                    *rings,
+                    (*light).into(),
+                    (*dark).into(),
+                ))
+            }
+            TextureDescription::Cloud {
+                seed,
+                scale,
+                octaves,
+                sky,
+                cloud,
+            } => {
+                ensure_noise_scale(*scale, entry)?;
+                ensure(*octaves > 0, entry, "octaves must be positive")?;
+                ensure(
+                    is_color(*sky) && is_color(*cloud),
+                    entry,
+                    "colors must not be negative",
+                )?;
+                Arc::new(CloudTexture::new(
+                    perlin(*seed),
+                    *scale,
+                    *octaves,
+                    (*sky).into(),
+                    (*cloud).into(),
+                ))
+            }
+        })
+    }
+}
+
+impl SceneDescription {
+    fn build(self, base_dir: &Path) -> Result<Scene, SceneError> {
+        let (bounds, params, display) = self.render.build()?;
+        let camera = self.camera.build(self.render.aspect_ratio)?;
+
+        let mut textures = Textures {
+            base_dir,
+            descriptions: &self.textures,
+            built: HashMap::new(),
+            in_progress: Vec::new(),
+        };
+        for name in self.textures.keys() {
+            textures.get(name, &format!("textures.{}", name))?;
+        }
+
+        let mut materials: HashMap<String, SharedMaterial> = HashMap::new();
+        let mut emissive = HashSet::new();
+        for (i, (name, material)) in self.materials.into_iter().enumerate() {
+            if let MaterialDescription::DiffuseLight { .. } = material {
+                emissive.insert(name.clone());
+            }
+            let entry = format!("materials.{}", name);
+            let material = material.build(&mut textures, &entry)?;
+            materials.insert(name, Arc::new(TaggedMaterial::new(material, i as u32 + 1)));
+        }
+
+        let mut world: Vec<Box<dyn Hittable + Send + Sync>> = Vec::new();
+        let mut lights: Vec<Box<dyn Hittable + Send + Sync>> = Vec::new();
+        for (
+            i,
+            ObjectEntry {
+                object,
+                transform,
+                medium,
+            },
+        ) in self.objects.into_iter().enumerate()
+        {
+            let entry = format!("objects[{}]", i);
+            let transform = build_transform(&transform, &entry)?;
+            let mut objects: Vec<Box<dyn Hittable + Send + Sync>> = Vec::new();
+            let lookup = |name: &str| {
+                materials
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| invalid(&entry, format!("unknown material `{}`", name)))
+            };
+            let is_light =
+                |name: &str| transform.is_none() && medium.is_none() && emissive.contains(name);
+
+            match object {
+                ObjectDescription::Sphere {
+                    center,
+                    radius,
+                    material,
+                } => {
+                    ensure(
+                        radius.is_finite() && radius != 0.0,
+                        &entry,
+                        "radius must be non-zero",
+                    )?;
+                    objects.push(Box::new(Sphere::new(
+                        center.into(),
+                        radius,
+                        lookup(&material)?,
+                    )));
+                    if is_light(&material) {
+                        lights.push(Box::new(Sphere::new(
+                            center.into(),
+                            radius,
+                            lookup(&material)?,
+                        )));
+                    }
+                }
+                ObjectDescription::MovingSphere {
+                    center0,
+                    center1,
+                    time0,
+                    time1,
+                    radius,
+                    material,
+                } => {
+                    ensure(
+                        radius.is_finite() && radius != 0.0,
+                        &entry,
+                        "radius must be non-zero",
+                    )?;
+                    ensure(
+                        time0.is_finite() && time1.is_finite() && time0 < time1,
+                        &entry,
+                        "time0 must be before time1",
+                    )?;
+                    objects.push(Box::new(MovingSphere::new(
+                        center0.into(),
+                        center1.into(),
+                        time0,
+                        time1,
+                        radius,
+                        lookup(&material)?,
+                    )));
+                }
+                ObjectDescription::Triangle { vertices, material } => {
+                    let [v0, v1, v2] = vertices;
+                    let (v0, v1, v2) = (Point3::from(v0), Point3::from(v1), Point3::from(v2));
+                    ensure(
+                        (v1 - v0).cross(v2 - v0).length_squared() > 0.0,
+                        &entry,
+                        "triangle is degenerate",
+                    )?;
+                    objects.push(Box::new(Triangle::new(v0, v1, v2, lookup(&material)?)));
+                }
+                ObjectDescription::Quad { q, u, v, material } => {
+                    let (u, v) = (Vec3::from(u), Vec3::from(v));
+                    ensure(
+                        u.cross(v).length_squared() > 0.0,
+                        &entry,
+                        "quad is degenerate",
+                    )?;
+                    objects.push(Box::new(Quad::new(q.into(), u, v, lookup(&material)?)));
+                    if is_light(&material) {
+                        lights.push(Box::new(Quad::new(q.into(), u, v, lookup(&material)?)));
+                    }
+                }
+                ObjectDescription::Disk {
+                    center,
+                    normal,
+                    radius,
+                    material,
+                } => {
+                    ensure(
+                        Vec3::from(normal).length_squared() > 0.0,
+                        &entry,
+                        "normal must be non-zero",
+                    )?;
+                    ensure(
+                        radius.is_finite() && radius > 0.0,
+                        &entry,
+                        "radius must be positive",
+                    )?;
+                    objects.push(Box::new(Disk::new(
+                        center.into(),
+                        normal.into(),
+                        radius,
+                        lookup(&material)?,
+                    )));
+                }
+                ObjectDescription::Plane {
+                    point,
+                    normal,
+                    material,
+                } => {
+                    ensure(
+                        Vec3::from(normal).length_squared() > 0.0,
+                        &entry,
+                        "normal must be non-zero",
+                    )?;
+                    objects.push(Box::new(Plane::new(
+                        point.into(),
+                        normal.into(),
+                        lookup(&material)?,
+                    )));
+                }
+                ObjectDescription::Cuboid { min, max, material } => {
+                    ensure(
+                        (0..3).all(|axis| min[axis] < max[axis]),
+                        &entry,
+                        "min must be below max on every axis",
+                    )?;
+                    objects.push(Box::new(Cuboid::new(
+                        min.into(),
+                        max.into(),
+                        lookup(&material)?,
+                    )));
+                }
+                ObjectDescription::Mesh { path } => {
+                    let meshes =
+                        load_obj(base_dir.join(path)).map_err(|source| SceneError::Mesh {
+                            entry: entry.clone(),
+                            source,
+                        })?;
+                    objects.extend(meshes);
+                }
+            }
+
+            let mut objects = match transform {
+                Some(transform) => {
+                    let instance: Box<dyn Hittable + Send + Sync> =
+                        Box::new(Instance::new(objects, transform));
+                    vec![instance]
+                }
+                None => objects,
+            };
+            if let Some(MediumDescription { density, material }) = medium {
+                ensure(
+                    density.is_finite() && density > 0.0,
+                    &entry,
+                    "medium density must be positive",
+                )?;
+                let phase_function = lookup(&material)?;
+                objects = vec![Box::new(ConstantMedium::with_phase_function(
+                    objects,
+                    density,
+                    phase_function,
+                ))];
+            }
+            world.push(Box::new(TaggedHittable::new(objects, i as u32 + 1)));
+        }
+
+        Ok(Scene {
+            bounds,
+            params,
+            display,
+            aovs: self.render.aovs,
+            denoise: self.render.denoise,
+            camera,
+            world,
+            lights,
+        })
+    }
+}
+
+impl RenderDescription {
+    fn build(&self) -> Result<(Bounds, RenderParams, DisplayTransform), SceneError> {
+        // Rendering spreads pixels from one edge of the view to the other, so
+        // it needs at least two of them in each direction.
+        ensure(self.width >= 2, "render.width", "must be at least 2")?;
+        ensure(
+            self.aspect_ratio.is_finite() && self.aspect_ratio > 0.0,
+            "render.aspect_ratio",
+            "must be positive",
+        )?;
+        let height = (self.width as f64 / self.aspect_ratio) as u32;
+        ensure(
+            height >= 2,
+            "render.aspect_ratio",
+            "leaves the image less than 2 pixels high",
+        )?;
+        ensure(
+            self.samples_per_pixel > 0,
+            "render.samples_per_pixel",
+            "must be positive",
+        )?;
+        ensure(self.depth > 0, "render.depth", "must be positive")?;
+
+        let bounds = Bounds {
+            width: self.width,
+            height,
+        };
+        let background = match self.background {
+            Some(color) => {
+                ensure(is_color(color), "render.background", "must not be negative")?;
+                Background::Solid(color.into())
+            }
+            None => Background::Sky,
+        };
+
+        let integrator = match self.integrator {
+            None | Some(IntegratorDescription::Path) => IntegratorKind::Path,
+            Some(IntegratorDescription::AmbientOcclusion { distance }) => {
+                ensure(
+                    distance > 0.0,
+                    "render.integrator",
+                    "distance must be positive",
+                )?;
+                IntegratorKind::AmbientOcclusion { distance }
+            }
+            Some(IntegratorDescription::Whitted) => IntegratorKind::Whitted,
+            Some(IntegratorDescription::Normals) => IntegratorKind::Normals,
+            Some(IntegratorDescription::Depth) => IntegratorKind::Depth,
+            Some(IntegratorDescription::MaterialIds) => IntegratorKind::MaterialIds,
+        };
+
+        let adaptive = match &self.adaptive {
+            Some(adaptive) => {
+                ensure(
+                    adaptive.threshold > 0.0 && adaptive.threshold.is_finite(),
+                    "render.adaptive",
+                    "threshold must be positive",
+                )?;
+                ensure(
+                    adaptive.min_samples > 0,
+                    "render.adaptive",
+                    "min_samples must be positive",
+                )?;
+                Some(AdaptiveSampling {
+                    min_samples: adaptive.min_samples,
+                    threshold: adaptive.threshold,
+                })
+            }
+            None => None,
+        };
+
+        let params = RenderParams {
+            samples_per_pixel: self.samples_per_pixel,
+            depth: self.depth,
+            background,
+            integrator,
+            seed: self.seed,
+            adaptive,
+        };
+        ensure(
+            self.exposure.is_finite(),
+            "render.exposure",
+            "must be a number",
+        )?;
+        for (i, aov) in self.aovs.iter().enumerate() {
+            ensure(
+                !self.aovs[..i].contains(aov),
+                "render.aovs",
+                "lists an AOV twice",
+            )?;
+        }
+
+        let display = DisplayTransform {
+            exposure: self.exposure,
+            tone_map: self.tone_map,
+        };
+        Ok((bounds, params, display))
+    }
+}
+
+impl CameraDescription {
+    fn build(&self, aspect_ratio: f64) -> Result<Camera, SceneError> {
+        let look_from = Point3::from(self.look_from);
+        let look_at = Point3::from(self.look_at);
+        let v_up = Vec3::from(self.v_up);
+        let view = look_from - look_at;
+
+        ensure(
+            view.length_squared() > 0.0,
+            "camera",
+            "look_from and look_at must differ",
+        )?;
+        ensure(
+            v_up.cross(view).length_squared() > 0.0,
+            "camera.v_up",
+            "must not be parallel to the view direction",
+        )?;
+        ensure(
+            self.vertical_fov > 0.0 && self.vertical_fov < 180.0,
+            "camera.vertical_fov",
+            "must be between 0 and 180 degrees",
+        )?;
+        ensure(
+            self.aperture.is_finite() && self.aperture >= 0.0,
+            "camera.aperture",
+            "must not be negative",
+        )?;
+        let focus_distance = self.focus_distance.unwrap_or_else(|| view.length());
+        ensure(
+            focus_distance.is_finite() && focus_distance > 0.0,
+            "camera.focus_distance",
+            "must be positive",
+        )?;
+        let [open, close] = self.shutter;
+        ensure(
+            open.is_finite() && close.is_finite() && open <= close,
+            "camera.shutter",
+            "must be an [open, close] interval",
+        )?;
+
+        Ok(Camera::new(
+            look_from,
+            look_at,
+            v_up,
+            self.vertical_fov,
+            aspect_ratio,
+            self.aperture,
+            focus_distance,
+        )
+        .with_shutter(open, close))
+    }
+}
+
+impl MaterialDescription {
+    fn build(self, textures: &mut Textures, entry: &str) -> Result<SharedMaterial, SceneError> {
+        Ok(match self {
+            MaterialDescription::Lambertian { albedo } => {
+                let albedo = textures.resolve(&albedo, entry, "albedo")?;
+                Arc::new(Lambertian::textured(albedo))
+            }
+            MaterialDescription::Metal { albedo, fuzz } => {
+                let albedo = textures.resolve(&albedo, entry, "albedo")?;
+                ensure(
+                    (0.0..=1.0).contains(&fuzz),
+                    entry,
+                    "fuzz must be between 0 and 1",
+                )?;
+                Arc::new(Metal::textured(albedo, fuzz))
+            }
+            MaterialDescription::Dielectric {
+                index_of_refraction,
+            } => {
+                ensure(
+                    index_of_refraction.is_finite() && index_of_refraction > 0.0,
+                    entry,
+                    "index_of_refraction must be positive",
+                )?;
+                Arc::new(Dielectric::new(index_of_refraction))
+            }
+            MaterialDescription::DiffuseLight { emit } => {
+                let emit = textures.resolve(&emit, entry, "emit")?;
+                Arc::new(DiffuseLight::textured(emit))
+            }
+            MaterialDescription::Isotropic { albedo } => {
+                let albedo = textures.resolve(&albedo, entry, "albedo")?;
+                Arc::new(Isotropic::textured(albedo))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ray::Ray;
+    use crate::vec3::Color;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const SCENE: &str = r#"
+[render]
+width = 400
+aspect_ratio = 2.0
+samples_per_pixel = 10
+
+[camera]
+look_from = [0.0, 0.0, 1.0]
+look_at = [0.0, 0.0, -1.0]
+vertical_fov = 90.0
+
+[materials.red]
+type = "lambertian"
+albedo = [0.8, 0.1, 0.1]
+
+[materials.mirror]
+type = "metal"
+albedo = [0.9, 0.9, 0.9]
+
+[[objects]]
+type = "sphere"
+center = [0.0, 0.0, -1.0]
+radius = 0.5
+material = "red"
+
+[[objects]]
+type = "triangle"
+vertices = [[-1.0, -1.0, -3.0], [1.0, -1.0, -3.0], [0.0, 1.0, -3.0]]
+material = "mirror"
+"#;
+
+    fn parse(source: &str) -> Result<Scene, SceneError> {
+        parse_scene(source, Path::new(""))
+    }
+
+    fn invalid_entry(source: &str) -> String {
+        match parse(source) {
+            Err(SceneError::Invalid { entry, .. }) => entry,
+            Err(err) => panic!("expected an Invalid error, got {}", err),
+            Ok(_) => panic!("expected an Invalid error"),
+        }
+    }
+
+    #[test]
+    fn test_parse_scene() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let scene = parse(SCENE).unwrap();
+        assert_eq!(
+            Bounds {
+                width: 400,
+                height: 200
+            },
+            scene.bounds
+        );
+        assert_eq!(
+            RenderParams {
+                samples_per_pixel: 10,
+                depth: 50,
+                background: Background::Sky,
+                integrator: IntegratorKind::Path,
+                seed: 0,
+                adaptive: None,
+            },
+            scene.params
+        );
+        assert_eq!(2, scene.world.len());
+
+        let ray = Ray::new(Point3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let record = scene
+            .world
+            .hit(&ray, 0.001, f64::INFINITY, &mut rng)
+            .unwrap();
+        assert!(approx_eq!(f64, record.t, 1.5, ulps = 2));
+    }
+
+    #[test]
+    fn test_unknown_material() {
+        let source = SCENE.replace("material = \"mirror\"", "material = \"gold\"");
+        assert_eq!("objects[1]", invalid_entry(&source));
+    }
+
+    #[test]
+    fn test_invalid_material() {
+        let source = SCENE.replace(
+            "albedo = [0.9, 0.9, 0.9]",
+            "albedo = [0.9, 0.9, 0.9]\nfuzz = 2.0",
+        );
+        assert_eq!("materials.mirror", invalid_entry(&source));
+    }
+
+    #[test]
+    fn test_invalid_camera() {
+        let source = SCENE.replace("vertical_fov = 90.0", "vertical_fov = 180.0");
+        assert_eq!("camera.vertical_fov", invalid_entry(&source));
+    }
+
+    #[test]
+    fn test_invalid_render() {
+        let source = SCENE.replace("samples_per_pixel = 10", "samples_per_pixel = 0");
+        assert_eq!("render.samples_per_pixel", invalid_entry(&source));
+        let source = SCENE.replace("width = 400", "width = 1");
+        assert_eq!("render.width", invalid_entry(&source));
+        let source = SCENE.replace("aspect_ratio = 2.0", "aspect_ratio = 300.0");
+        assert_eq!("render.aspect_ratio", invalid_entry(&source));
+    }
+
+    #[test]
+    fn test_background() {
+        let source = SCENE.replace(
+            "samples_per_pixel = 10",
+            "samples_per_pixel = 10\nbackground = [0.0, 0.0, 0.0]",
+        );
+        let scene = parse(&source).unwrap();
+        assert_eq!(Background::Solid(Color::default()), scene.params.background);
+
+        let source = SCENE.replace(
+            "samples_per_pixel = 10",
+            "samples_per_pixel = 10\nbackground = [-1.0, 0.0, 0.0]",
+        );
+        assert_eq!("render.background", invalid_entry(&source));
+    }
+
+    #[test]
+    fn test_integrator() {
+        let with = |integrator: &str| {
+            SCENE.replace(
+                "samples_per_pixel = 10",
+                &format!("samples_per_pixel = 10\nintegrator = {}", integrator),
+            )
+        };
+        let scene = parse(&with(r#"{ type = "normals" }"#)).unwrap();
+        assert_eq!(IntegratorKind::Normals, scene.params.integrator);
+
+        let source = with(r#"{ type = "ambient_occlusion", distance = 0.5 }"#);
+        let scene = parse(&source).unwrap();
+        assert_eq!(
+            IntegratorKind::AmbientOcclusion { distance: 0.5 },
+            scene.params.integrator
+        );
+
+        let source = source.replace("distance = 0.5", "distance = 0.0");
+        assert_eq!("render.integrator", invalid_entry(&source));
+        let source = with(r#"{ type = "bidirectional" }"#);
+        assert!(matches!(parse(&source), Err(SceneError::Parse(_))));
+    }
+
+    #[test]
+    fn test_aovs() {
+        let mut rng = StdRng::seed_from_u64(0);
+        assert!(parse(SCENE).unwrap().aovs.is_empty());
+        let with = |aovs: &str| {
+            SCENE.replace(
+                "samples_per_pixel = 10",
+                &format!("samples_per_pixel = 10\naovs = {}", aovs),
+            )
+        };
+        let scene = parse(&with(r#"["depth", "object_id"]"#)).unwrap();
+        assert_eq!(vec![Aov::Depth, Aov::ObjectId], scene.aovs);
+        assert_eq!("render.aovs", invalid_entry(&with(r#"["depth", "depth"]"#)));
+
+        // The first object is the red sphere, and `red` sorts after `mirror`.
+        let ray = Ray::new(Point3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let record = scene
+            .world
+            .hit(&ray, 0.001, f64::INFINITY, &mut rng)
+            .unwrap();
+        assert_eq!(1, record.object_id);
+        assert_eq!(2, record.material.id());
+    }
+
+    #[test]
+    fn test_adaptive() {
+        let with = |adaptive: &str| {
+            SCENE.replace(
+                "samples_per_pixel = 10",
+                &format!("samples_per_pixel = 10\nadaptive = {}", adaptive),
+            )
+        };
+        let scene = parse(&with("{ threshold = 0.01 }")).unwrap();
+        assert_eq!(
+            Some(AdaptiveSampling {
+                min_samples: 16,
+                threshold: 0.01,
+            }),
+            scene.params.adaptive
+        );
+        let scene = parse(&with("{ threshold = 0.01, min_samples = 4 }")).unwrap();
+        assert_eq!(4, scene.params.adaptive.unwrap().min_samples);
+
+        assert_eq!(
+            "render.adaptive",
+            invalid_entry(&with("{ threshold = 0.0 }"))
+        );
+        let source = with("{ threshold = 0.01, min_samples = 0 }");
+        assert_eq!("render.adaptive", invalid_entry(&source));
+    }
+
+    #[test]
+    fn test_display_transform() {
+        assert_eq!(DisplayTransform::default(), parse(SCENE).unwrap().display);
+
+        let source = SCENE.replace(
+            "samples_per_pixel = 10",
+            "samples_per_pixel = 10\nexposure = -1.5\ntone_map = \"aces\"",
+        );
+        assert_eq!(
+            DisplayTransform {
+                exposure: -1.5,
+                tone_map: ToneMap::Aces,
+            },
+            parse(&source).unwrap().display
+        );
+
+        let source = SCENE.replace(
+            "samples_per_pixel = 10",
+            "samples_per_pixel = 10\nexposure = nan",
+        );
+        assert_eq!("render.exposure", invalid_entry(&source));
+        let source = SCENE.replace(
+            "samples_per_pixel = 10",
+            "samples_per_pixel = 10\ntone_map = \"linear\"",
+        );
+        assert!(matches!(parse(&source), Err(SceneError::Parse(_))));
+    }
+
+    #[test]
+    fn test_textures() {
+        let source = format!(
+            "{}\n{}",
+            SCENE.replace("albedo = [0.8, 0.1, 0.1]", "albedo = \"board\""),
+            r#"
+[textures.board]
+type = "checker"
+scale = 1.0
+even = "white"
+odd = [0.0, 0.0, 0.0]
+
+[textures.white]
+type = "solid"
+color = [1.0, 1.0, 1.0]
+"#
+        );
+        assert!(parse(&source).is_ok());
+
+        let source = source.replace("albedo = \"board\"", "albedo = \"plaid\"");
+        assert_eq!("materials.red", invalid_entry(&source));
+
+        let source = source.replace("even = \"white\"", "even = \"board\"");
+        assert_eq!("textures.board", invalid_entry(&source));
+    }
+
+    #[test]
+    fn test_image_texture() {
+        let dir = std::env::temp_dir().join(format!("rtiow-scene-image-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        image::RgbImage::from_pixel(4, 2, image::Rgb([0, 0, 255]))
+            .save(dir.join("earthmap.png"))
+            .unwrap();
+
+        let source = format!(
+            "{}\n{}",
+            SCENE.replace("albedo = [0.8, 0.1, 0.1]", "albedo = \"earth\""),
+            r#"
+[textures.earth]
+type = "image"
+path = "earthmap.png"
+wrap = "clamp"
+"#
+        );
+        let result = parse_scene(&source, &dir);
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(result.is_ok());
+
+        match parse(&source) {
+            Err(SceneError::Image { entry, .. }) => assert_eq!("textures.earth", entry),
+            _ => panic!("expected an Image error"),
+        }
+    }
+
+    #[test]
+    fn test_noise_textures() {
+        let source = format!(
+            "{}\n{}",
+            SCENE.replace("albedo = [0.8, 0.1, 0.1]", "albedo = \"marble\""),
+            r#"
+[textures.marble]
+type = "marble"
+seed = 3
+scale = 4.0
+base = [0.9, 0.9, 0.9]
+vein = [0.1, 0.1, 0.1]
+
+[textures.wood]
+type = "wood"
+rings = 5.0
+light = [0.8, 0.6, 0.3]
+dark = [0.4, 0.2, 0.1]
+
+[textures.sky]
+type = "cloud"
+sky = [0.3, 0.5, 0.9]
+cloud = [1.0, 1.0, 1.0]
+
+[textures.grey]
+type = "noise"
+"#
+        );
+        assert!(parse(&source).is_ok());
+
+        let source = source.replace("rings = 5.0", "rings = 0.0");
+        assert_eq!("textures.wood", invalid_entry(&source));
+    }
+
+    #[test]
+    fn test_motion_blur() {
+        let source = format!(
+            "{}\n{}",
+            SCENE.replace(
+                "vertical_fov = 90.0",
+                "vertical_fov = 90.0\nshutter = [0.0, 1.0]"
+            ),
+            r#"
+[[objects]]
+type = "moving_sphere"
+center0 = [2.0, 0.0, -1.0]
+center1 = [2.0, 1.0, -1.0]
+radius = 0.5
+material = "red"
+"#
+        );
+        let scene = parse(&source).unwrap();
+        assert_eq!(3, scene.world.len());
+
+        let source = source.replace("shutter = [0.0, 1.0]", "shutter = [1.0, 0.0]");
+        assert_eq!("camera.shutter", invalid_entry(&source));
+    }
+
+    #[test]
+    fn test_transform() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let source = SCENE.replace(
+            "center = [0.0, 0.0, -1.0]\nradius = 0.5\nmaterial = \"red\"",
+            r#"center = [0.0, 0.0, 0.0]
+radius = 0.5
+material = "red"
+transform = [
+    { scale = [2.0, 1.0, 1.0] },
+    { rotate = { axis = [0.0, 1.0, 0.0], degrees = 90.0 } },
+    { translate = [0.0, 0.0, -1.0] },
+]"#,
+        );
+        let scene = parse(&source).unwrap();
+
+        // The sphere is stretched along x, turned so the stretch lies along z,
+        // then moved to z = -1: its front is at z = 0 rather than -0.5.
+        let ray = Ray::new(Point3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let record = scene
+            .world
+            .hit(&ray, 0.001, f64::INFINITY, &mut rng)
+            .unwrap();
+        assert!(approx_eq!(f64, record.t, 1.0, epsilon = 1e-9));
+
+        let source = source.replace("scale = [2.0, 1.0, 1.0]", "scale = [2.0, 0.0, 1.0]");
+        assert_eq!("objects[0]", invalid_entry(&source));
+    }
+
+    #[test]
+    fn test_planar_objects() {
+        let source = format!(
+            "{}\n{}",
+            SCENE,
+            r#"
+[[objects]]
+type = "quad"
+q = [-1.0, -1.0, -4.0]
+u = [2.0, 0.0, 0.0]
+v = [0.0, 2.0, 0.0]
+material = "red"
+
+[[objects]]
+type = "disk"
+center = [0.0, 0.0, -5.0]
+normal = [0.0, 0.0, 1.0]
+radius = 1.0
+material = "red"
+
+[[objects]]
+type = "plane"
+point = [0.0, -1.0, 0.0]
+normal = [0.0, 1.0, 0.0]
+material = "red"
+
+[[objects]]
+type = "box"
+min = [-0.5, -0.5, -7.0]
+max = [0.5, 0.5, -6.0]
+material = "red"
+"#
+        );
+        let scene = parse(&source).unwrap();
+        assert_eq!(6, scene.world.len());
+
+        let source = source.replace("max = [0.5, 0.5, -6.0]", "max = [0.5, -0.5, -6.0]");
+        assert_eq!("objects[5]", invalid_entry(&source));
+    }
+
+    #[test]
+    fn test_medium() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let source = format!(
+            "{}\n{}",
+            SCENE.replace(
+                "radius = 0.5\nmaterial = \"red\"",
+                "radius = 0.5\nmaterial = \"red\"\nmedium = { density = 1000.0, material = \"smoke\" }",
+            ),
+            "[materials.smoke]\ntype = \"isotropic\"\nalbedo = [0.5, 0.5, 0.5]\n"
+        );
+        let scene = parse(&source).unwrap();
+        assert_eq!(2, scene.world.len());
+
+        // Dense enough that the ray scatters just inside the sphere's surface.
+        let ray = Ray::new(Point3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let record = scene
+            .world
+            .hit(&ray, 0.001, f64::INFINITY, &mut rng)
+            .unwrap();
+        assert!(record.t >= 1.5 && record.t < 1.6, "t = {}", record.t);
+
+        let source = source.replace("density = 1000.0", "density = -1.0");
+        assert_eq!("objects[0]", invalid_entry(&source));
+    }
+
+    #[test]
+    fn test_lights() {
+        assert!(parse(SCENE).unwrap().lights.is_empty());
+
+        let source = format!(
+            "{}\n{}",
+            SCENE.replace("material = \"red\"", "material = \"lamp\""),
+            "[materials.lamp]\ntype = \"diffuse_light\"\nemit = [4.0, 4.0, 4.0]\n"
+        );
+        let scene = parse(&source).unwrap();
+        assert_eq!(2, scene.world.len());
+        assert_eq!(1, scene.lights.len());
+
+        // Transformed lights are not sampled directly.
+        let source = source.replace(
+            "material = \"lamp\"",
+            "material = \"lamp\"\ntransform = [{ translate = [0.0, 1.0, 0.0] }]",
+        );
+        assert!(parse(&source).unwrap().lights.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_field() {
+        let source = SCENE.replace("radius = 0.5", "radius = 0.5\nradios = 0.5");
+        assert!(matches!(parse(&source), Err(SceneError::Parse(_))));
+    }
+
+    #[test]
+    fn test_missing_mesh() {
+        let source = format!(
+            "{}\n[[objects]]\ntype = \"mesh\"\npath = \"nope.obj\"\n",
+            SCENE
+        );
+        match parse(&source) {
+            Err(SceneError::Mesh { entry, .. }) => assert_eq!("objects[2]", entry),
+            _ => panic!("expected a Mesh error"),
+        }
+    }
+
+    #[test]
+    fn test_example_scenes_load() {
+        let scenes = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenes");
+        for entry in fs::read_dir(scenes).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|ext| ext == "toml") {
+                if let Err(err) = load_scene(&path) {
+                    panic!("{}: {}", path.display(), err);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,21 @@
+pub mod checker;
+pub mod image_texture;
+pub mod noise;
+pub mod perlin;
+pub mod solid_color;
+
+use crate::vec3::{Color, Point3};
+use std::sync::Arc;
+
+/// A color that varies over a surface, looked up by surface coordinates
+/// `(u, v)` and/or the hit point `p`.
+pub trait Texture {
+    fn value(&self, u: f64, v: f64, p: Point3) -> Color;
+}
+
+/// Lets one texture be shared between several materials.
+impl<T: Texture + ?Sized> Texture for Arc<T> {
+    fn value(&self, u: f64, v: f64, p: Point3) -> Color {
+        (**self).value(u, v, p)
+    }
+}
@@ -0,0 +1,50 @@
+use crate::texture::Texture;
+use crate::vec3::{Color, Point3};
+
+/// A 3D checkerboard of unit cubes `scale` wide, alternating between two
+/// textures. Being solid rather than UV-mapped, it looks the same on any shape.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CheckerTexture<E: Texture, O: Texture> {
+    pub scale: f64,
+    pub even: E,
+    pub odd: O,
+}
+
+impl<E: Texture, O: Texture> CheckerTexture<E, O> {
+    pub fn new(scale: f64, even: E, odd: O) -> Self {
+        CheckerTexture { scale, even, odd }
+    }
+}
+
+impl<E: Texture, O: Texture> Texture for CheckerTexture<E, O> {
+    fn value(&self, u: f64, v: f64, p: Point3) -> Color {
+        let inv_scale = 1.0 / self.scale;
+        let x = (inv_scale * p.x).floor() as i64;
+        let y = (inv_scale * p.y).floor() as i64;
+        let z = (inv_scale * p.z).floor() as i64;
+
+        if (x + y + z) % 2 == 0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::texture::solid_color::SolidColor;
+
+    #[test]
+    fn test_checker_alternates() {
+        let white = Color::new(1.0, 1.0, 1.0);
+        let black = Color::new(0.0, 0.0, 0.0);
+        let checker = CheckerTexture::new(0.5, SolidColor::new(white), SolidColor::new(black));
+
+        assert_eq!(white, checker.value(0.0, 0.0, Point3::new(0.1, 0.1, 0.1)));
+        assert_eq!(black, checker.value(0.0, 0.0, Point3::new(0.6, 0.1, 0.1)));
+        assert_eq!(white, checker.value(0.0, 0.0, Point3::new(0.6, 0.6, 0.1)));
+        assert_eq!(black, checker.value(0.0, 0.0, Point3::new(-0.1, 0.1, 0.1)));
+    }
+}
@@ -0,0 +1,281 @@
+use crate::texture::Texture;
+use crate::vec3::{Color, Point3};
+use image::codecs::hdr::HdrDecoder;
+use image::ImageResult;
+use serde::Deserialize;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::Arc;
+
+/// How texture coordinates outside `[0, 1]` are handled.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WrapMode {
+    /// Tile the image.
+    Repeat,
+    /// Extend the edge texels outwards.
+    Clamp,
+}
+
+/// How a texture coordinate between texel centers is resolved.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Filter {
+    /// Use the texel the coordinate falls in.
+    Nearest,
+    /// Blend the four closest texels.
+    Bilinear,
+}
+
+/// A texture backed by a grid of linear colors, stored top row first and
+/// mapped so that `(0, 0)` is the bottom-left corner.
+#[derive(Clone, Debug)]
+pub struct ImageTexture {
+    width: usize,
+    height: usize,
+    pixels: Arc<Vec<Color>>,
+    wrap_mode: WrapMode,
+    filter: Filter,
+}
+
+impl ImageTexture {
+    /// Wraps linear `pixels` in a repeating, bilinearly filtered texture.
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        assert!(width > 0 && height > 0);
+        assert_eq!(width * height, pixels.len());
+        ImageTexture {
+            width,
+            height,
+            pixels: Arc::new(pixels),
+            wrap_mode: WrapMode::Repeat,
+            filter: Filter::Bilinear,
+        }
+    }
+
+    /// Loads an image file. Radiance `.hdr` files are read as linear radiance;
+    /// any other format the `image` crate understands (PNG, JPEG, ...) is
+    /// treated as 8-bit sRGB and converted to linear.
+    pub fn open<P: AsRef<Path>>(path: P) -> ImageResult<Self> {
+        let path = path.as_ref();
+        let is_hdr = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("hdr"));
+
+        if is_hdr {
+            let decoder = HdrDecoder::new(BufReader::new(File::open(path)?))?;
+            let metadata = decoder.metadata();
+            let pixels = decoder
+                .read_image_hdr()?
+                .into_iter()
+                .map(|rgb| Color::new(rgb[0] as f64, rgb[1] as f64, rgb[2] as f64))
+                .collect();
+            return Ok(ImageTexture::new(
+                metadata.width as usize,
+                metadata.height as usize,
+                pixels,
+            ));
+        }
+
+        let image = image::open(path)?.to_rgb8();
+        let lookup: Vec<f64> = (0..=255u8).map(srgb_to_linear).collect();
+        let pixels = image
+            .pixels()
+            .map(|rgb| {
+                Color::new(
+                    lookup[rgb[0] as usize],
+                    lookup[rgb[1] as usize],
+                    lookup[rgb[2] as usize],
+                )
+            })
+            .collect();
+        Ok(ImageTexture::new(
+            image.width() as usize,
+            image.height() as usize,
+            pixels,
+        ))
+    }
+
+    pub fn with_wrap_mode(self, wrap_mode: WrapMode) -> Self {
+        ImageTexture { wrap_mode, ..self }
+    }
+
+    pub fn with_filter(self, filter: Filter) -> Self {
+        ImageTexture { filter, ..self }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Looks up a texel by possibly out-of-range coordinates, applying the wrap
+    /// mode.
+    fn texel(&self, x: i64, y: i64) -> Color {
+        let (width, height) = (self.width as i64, self.height as i64);
+        let (x, y) = match self.wrap_mode {
+            WrapMode::Repeat => (x.rem_euclid(width), y.rem_euclid(height)),
+            WrapMode::Clamp => (x.clamp(0, width - 1), y.clamp(0, height - 1)),
+        };
+        self.pixel(x as usize, y as usize)
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _p: Point3) -> Color {
+        // Image rows run top to bottom, while v runs bottom to top.
+        let x = u * self.width as f64;
+        let y = (1.0 - v) * self.height as f64;
+
+        match self.filter {
+            Filter::Nearest => self.texel(x.floor() as i64, y.floor() as i64),
+            Filter::Bilinear => {
+                // Texel centers sit at half-integer coordinates.
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (x0, y0) = (x.floor(), y.floor());
+                let (fx, fy) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as i64, y0 as i64);
+
+                let top = (1.0 - fx) * self.texel(x0, y0) + fx * self.texel(x0 + 1, y0);
+                let bottom = (1.0 - fx) * self.texel(x0, y0 + 1) + fx * self.texel(x0 + 1, y0 + 1);
+                (1.0 - fy) * top + fy * bottom
+            }
+        }
+    }
+}
+
+/// Decodes an 8-bit sRGB channel value to linear intensity.
+pub fn srgb_to_linear(value: u8) -> f64 {
+    let c = value as f64 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, Rgb};
+
+    const RED: Color = Color {
+        x: 1.0,
+        y: 0.0,
+        z: 0.0,
+    };
+    const GREEN: Color = Color {
+        x: 0.0,
+        y: 1.0,
+        z: 0.0,
+    };
+    const BLUE: Color = Color {
+        x: 0.0,
+        y: 0.0,
+        z: 1.0,
+    };
+    const WHITE: Color = Color {
+        x: 1.0,
+        y: 1.0,
+        z: 1.0,
+    };
+
+    /// Top row: red, green. Bottom row: blue, white.
+    fn quadrants() -> ImageTexture {
+        ImageTexture::new(2, 2, vec![RED, GREEN, BLUE, WHITE])
+    }
+
+    fn assert_color_eq(exp: Color, act: Color) {
+        for i in 0..3 {
+            assert!(
+                approx_eq!(f64, exp[i], act[i], epsilon = 1e-9),
+                "expected {:?}, got {:?}",
+                exp,
+                act
+            );
+        }
+    }
+
+    #[test]
+    fn test_nearest_lookup() {
+        let texture = quadrants().with_filter(Filter::Nearest);
+        let p = Point3::default();
+
+        assert_eq!(BLUE, texture.value(0.25, 0.25, p));
+        assert_eq!(WHITE, texture.value(0.75, 0.25, p));
+        assert_eq!(RED, texture.value(0.25, 0.75, p));
+        assert_eq!(GREEN, texture.value(0.75, 0.75, p));
+    }
+
+    #[test]
+    fn test_wrap_modes() {
+        let repeat = quadrants().with_filter(Filter::Nearest);
+        let clamp = repeat.clone().with_wrap_mode(WrapMode::Clamp);
+        let p = Point3::default();
+
+        assert_eq!(BLUE, repeat.value(1.25, 0.25, p));
+        assert_eq!(WHITE, clamp.value(1.25, 0.25, p));
+        assert_eq!(GREEN, repeat.value(-0.25, 0.75, p));
+        assert_eq!(RED, clamp.value(-0.25, 0.75, p));
+    }
+
+    #[test]
+    fn test_bilinear_filter() {
+        let texture = quadrants();
+        let p = Point3::default();
+
+        // Texel centers return the texel itself.
+        assert_color_eq(BLUE, texture.value(0.25, 0.25, p));
+        // The middle of the image blends all four texels equally.
+        assert_color_eq(
+            0.25 * (RED + GREEN + BLUE + WHITE),
+            texture.value(0.5, 0.5, p),
+        );
+        // Halfway between the bottom texels, clamped vertically.
+        let clamped = texture.with_wrap_mode(WrapMode::Clamp);
+        assert_color_eq(0.5 * (BLUE + WHITE), clamped.value(0.5, 0.0, p));
+    }
+
+    #[test]
+    fn test_srgb_to_linear() {
+        assert!(approx_eq!(f64, 0.0, srgb_to_linear(0), ulps = 2));
+        assert!(approx_eq!(f64, 1.0, srgb_to_linear(255), ulps = 2));
+        assert!(approx_eq!(f64, 0.5029, srgb_to_linear(188), epsilon = 1e-4));
+    }
+
+    #[test]
+    fn test_open_png() {
+        let path = std::env::temp_dir().join(format!("rtiow-texture-{}.png", std::process::id()));
+        let image: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::from_fn(2, 1, |x, _| {
+            if x == 0 {
+                Rgb([188, 0, 0])
+            } else {
+                Rgb([0, 0, 255])
+            }
+        });
+        image.save(&path).unwrap();
+
+        let texture = ImageTexture::open(&path);
+        std::fs::remove_file(&path).unwrap();
+        let texture = texture.unwrap();
+        assert_eq!((2, 1), (texture.width(), texture.height()));
+        assert_color_eq(
+            Color::new(srgb_to_linear(188), 0.0, 0.0),
+            texture.pixel(0, 0),
+        );
+        assert_color_eq(BLUE, texture.pixel(1, 0));
+    }
+
+    #[test]
+    fn test_open_missing_file() {
+        assert!(ImageTexture::open("does/not/exist.png").is_err());
+        assert!(ImageTexture::open("does/not/exist.hdr").is_err());
+    }
+}
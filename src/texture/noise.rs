@@ -0,0 +1,157 @@
+//! Procedural textures built on Perlin noise. Like the checker texture they are
+//! solid, so they need no UV mapping and no image files.
+
+use crate::texture::perlin::Perlin;
+use crate::texture::Texture;
+use crate::vec3::{Color, Point3};
+use std::f64::consts::PI;
+
+fn lerp(a: Color, b: Color, t: f64) -> Color {
+    (1.0 - t) * a + t * b
+}
+
+/// Grey noise, as in the book: `0.5 * (1 + noise(scale * p))`.
+#[derive(Clone, Debug)]
+pub struct NoiseTexture {
+    pub noise: Perlin,
+    pub scale: f64,
+}
+
+impl NoiseTexture {
+    pub fn new(noise: Perlin, scale: f64) -> Self {
+        NoiseTexture { noise, scale }
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f64, _v: f64, p: Point3) -> Color {
+        let t = 0.5 * (1.0 + self.noise.noise(self.scale * p));
+        Color::new(t, t, t)
+    }
+}
+
+/// Marble: sinusoidal bands along z, bent by turbulence into veins.
+#[derive(Clone, Debug)]
+pub struct MarbleTexture {
+    pub noise: Perlin,
+    pub scale: f64,
+    pub base: Color,
+    pub vein: Color,
+}
+
+impl MarbleTexture {
+    pub fn new(noise: Perlin, scale: f64, base: Color, vein: Color) -> Self {
+        MarbleTexture {
+            noise,
+            scale,
+            base,
+            vein,
+        }
+    }
+}
+
+impl Texture for MarbleTexture {
+    fn value(&self, _u: f64, _v: f64, p: Point3) -> Color {
+        let phase = self.scale * p.z + 10.0 * self.noise.turbulence(p, 7);
+        lerp(self.vein, self.base, 0.5 * (1.0 + phase.sin()))
+    }
+}
+
+/// Wood: concentric growth rings around the y axis, made irregular with a
+/// little turbulence.
+#[derive(Clone, Debug)]
+pub struct WoodTexture {
+    pub noise: Perlin,
+    pub scale: f64,
+    /// Rings per unit of distance from the axis, before scaling.
+    pub rings: f64,
+    pub light: Color,
+    pub dark: Color,
+}
+
+impl WoodTexture {
+    pub fn new(noise: Perlin, scale: f64, rings: f64, light: Color, dark: Color) -> Self {
+        WoodTexture {
+            noise,
+            scale,
+            rings,
+            light,
+            dark,
+        }
+    }
+}
+
+impl Texture for WoodTexture {
+    fn value(&self, _u: f64, _v: f64, p: Point3) -> Color {
+        let q = self.scale * p;
+        let radius = (q.x * q.x + q.z * q.z).sqrt();
+        let grain = self.rings * radius + 0.5 * self.noise.turbulence(q, 4);
+        lerp(self.light, self.dark, 0.5 - 0.5 * (2.0 * PI * grain).cos())
+    }
+}
+
+/// Clouds: fractal Brownian motion blending a sky color into a cloud color.
+#[derive(Clone, Debug)]
+pub struct CloudTexture {
+    pub noise: Perlin,
+    pub scale: f64,
+    pub octaves: usize,
+    pub sky: Color,
+    pub cloud: Color,
+}
+
+impl CloudTexture {
+    pub fn new(noise: Perlin, scale: f64, octaves: usize, sky: Color, cloud: Color) -> Self {
+        CloudTexture {
+            noise,
+            scale,
+            octaves,
+            sky,
+            cloud,
+        }
+    }
+}
+
+impl Texture for CloudTexture {
+    fn value(&self, _u: f64, _v: f64, p: Point3) -> Color {
+        let density = 0.5 + self.noise.fbm(self.scale * p, self.octaves, 2.0, 0.5);
+        lerp(self.sky, self.cloud, density.clamp(0.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn perlin() -> Perlin {
+        Perlin::new(&mut StdRng::seed_from_u64(42))
+    }
+
+    fn between(color: Color, a: Color, b: Color) -> bool {
+        (0..3).all(|i| color[i] >= a[i].min(b[i]) - 1e-12 && color[i] <= a[i].max(b[i]) + 1e-12)
+    }
+
+    #[test]
+    fn test_textures_stay_between_their_colors() {
+        let light = Color::new(0.9, 0.8, 0.6);
+        let dark = Color::new(0.3, 0.15, 0.05);
+        let textures: Vec<Box<dyn Texture>> = vec![
+            Box::new(MarbleTexture::new(perlin(), 4.0, light, dark)),
+            Box::new(WoodTexture::new(perlin(), 1.0, 6.0, light, dark)),
+            Box::new(CloudTexture::new(perlin(), 2.0, 6, light, dark)),
+        ];
+        let grey = NoiseTexture::new(perlin(), 4.0);
+
+        for i in 0..200 {
+            let t = i as f64 * 0.173;
+            let p = Point3::new(t.sin() * 3.0, t * 0.2 - 2.0, t.cos() * 3.0);
+            for texture in &textures {
+                assert!(between(texture.value(0.0, 0.0, p), light, dark));
+            }
+            let value = grey.value(0.0, 0.0, p);
+            assert!(between(value, Color::default(), Color::new(1.0, 1.0, 1.0)));
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use crate::vec3::{Point3, Vec3};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+const POINT_COUNT: usize = 256;
+
+/// Ken Perlin's gradient noise: a smooth pseudo-random function of space,
+/// roughly in `[-1, 1]`, with features about one unit apart.
+#[derive(Clone, Debug)]
+pub struct Perlin {
+    gradients: Vec<Vec3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>,
+}
+
+impl Perlin {
+    /// Builds the gradient and permutation tables from `rng`, so the same seed
+    /// always gives the same noise.
+    pub fn new<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let gradients = (0..POINT_COUNT)
+            .map(|_| loop {
+                let v = Vec3::random(rng, -1.0, 1.0);
+                let length_squared = v.length_squared();
+                if length_squared > 1e-6 && length_squared <= 1.0 {
+                    break v.unit_vector();
+                }
+            })
+            .collect();
+
+        Perlin {
+            gradients,
+            perm_x: Self::permutation(rng),
+            perm_y: Self::permutation(rng),
+            perm_z: Self::permutation(rng),
+        }
+    }
+
+    fn permutation<R: Rng + ?Sized>(rng: &mut R) -> Vec<usize> {
+        let mut perm: Vec<usize> = (0..POINT_COUNT).collect();
+        perm.shuffle(rng);
+        perm
+    }
+
+    pub fn noise(&self, p: Point3) -> f64 {
+        let floor = Point3::new(p.x.floor(), p.y.floor(), p.z.floor());
+        let f = p - floor;
+        let (i, j, k) = (floor.x as i64, floor.y as i64, floor.z as i64);
+
+        // Hermite smoothing hides the grid in the interpolation weights.
+        let smooth = |t: f64| t * t * (3.0 - 2.0 * t);
+        let (u, v, w) = (smooth(f.x), smooth(f.y), smooth(f.z));
+
+        let wrap = |n: i64| (n & (POINT_COUNT as i64 - 1)) as usize;
+        let mut sum = 0.0;
+        for di in 0..2 {
+            for dj in 0..2 {
+                for dk in 0..2 {
+                    let gradient = self.gradients[self.perm_x[wrap(i + di)]
+                        ^ self.perm_y[wrap(j + dj)]
+                        ^ self.perm_z[wrap(k + dk)]];
+                    let (di, dj, dk) = (di as f64, dj as f64, dk as f64);
+                    let weight = Vec3::new(f.x - di, f.y - dj, f.z - dk);
+                    sum += (di * u + (1.0 - di) * (1.0 - u))
+                        * (dj * v + (1.0 - dj) * (1.0 - v))
+                        * (dk * w + (1.0 - dk) * (1.0 - w))
+                        * gradient.dot(weight);
+                }
+            }
+        }
+        sum
+    }
+
+    /// Fractal Brownian motion: `octaves` layers of noise, each `lacunarity`
+    /// times finer and `gain` times weaker than the last.
+    pub fn fbm(&self, p: Point3, octaves: usize, lacunarity: f64, gain: f64) -> f64 {
+        let mut sum = 0.0;
+        let mut p = p;
+        let mut weight = 1.0;
+        for _ in 0..octaves {
+            sum += weight * self.noise(p);
+            p = lacunarity * p;
+            weight *= gain;
+        }
+        sum
+    }
+
+    /// Sum of the absolute value of `octaves` layers of noise, each twice as fine
+    /// and half as strong as the last. Always non-negative.
+    pub fn turbulence(&self, p: Point3, octaves: usize) -> f64 {
+        let mut sum = 0.0;
+        let mut p = p;
+        let mut weight = 1.0;
+        for _ in 0..octaves {
+            sum += weight * self.noise(p).abs();
+            p = 2.0 * p;
+            weight *= 0.5;
+        }
+        sum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn seeded(seed: u64) -> Perlin {
+        Perlin::new(&mut StdRng::seed_from_u64(seed))
+    }
+
+    #[test]
+    fn test_noise_is_seeded() {
+        let p = Point3::new(1.3, -2.7, 0.4);
+        assert_eq!(seeded(1).noise(p), seeded(1).noise(p));
+        assert_ne!(seeded(1).noise(p), seeded(2).noise(p));
+    }
+
+    #[test]
+    fn test_noise_range_and_lattice() {
+        let perlin = seeded(7);
+        // Gradient noise vanishes on the integer lattice.
+        assert_eq!(0.0, perlin.noise(Point3::new(3.0, -1.0, 4.0)));
+
+        for i in 0..1000 {
+            let t = i as f64 * 0.137;
+            let p = Point3::new(t, 0.5 * t - 3.0, 2.0 - 0.3 * t);
+            let n = perlin.noise(p);
+            assert!((-1.0..=1.0).contains(&n), "noise({:?}) = {}", p, n);
+            assert!(perlin.turbulence(p, 7) >= 0.0);
+        }
+    }
+
+    #[test]
+    fn test_single_octave() {
+        let perlin = seeded(3);
+        let p = Point3::new(0.3, 0.6, 0.9);
+        assert_eq!(perlin.noise(p), perlin.fbm(p, 1, 2.0, 0.5));
+        assert_eq!(perlin.noise(p).abs(), perlin.turbulence(p, 1));
+    }
+}
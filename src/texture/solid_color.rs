@@ -0,0 +1,25 @@
+use crate::texture::Texture;
+use crate::vec3::{Color, Point3};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SolidColor {
+    pub color: Color,
+}
+
+impl SolidColor {
+    pub fn new(color: Color) -> Self {
+        SolidColor { color }
+    }
+}
+
+impl From<Color> for SolidColor {
+    fn from(color: Color) -> Self {
+        SolidColor::new(color)
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _u: f64, _v: f64, _p: Point3) -> Color {
+        self.color
+    }
+}
@@ -0,0 +1,136 @@
+//! Turning linear radiance into colors for 8-bit displays.
+
+use crate::vec3::Color;
+use serde::Deserialize;
+
+/// How radiance is squeezed into the displayable range `[0, 1]`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToneMap {
+    /// Clips everything above one: exact for dim scenes, blown out for
+    /// bright ones.
+    #[default]
+    Clamp,
+    /// `x / (1 + x)`, which never quite reaches white.
+    Reinhard,
+    /// Krzysztof Narkowicz's fit of the ACES filmic curve.
+    Aces,
+    /// John Hable's filmic curve from Uncharted 2.
+    Hable,
+}
+
+/// Hable's curve before normalizing to its white point.
+fn hable_partial(x: f64) -> f64 {
+    let (a, b, c, d, e, f) = (0.15, 0.50, 0.10, 0.20, 0.02, 0.30);
+    ((x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f)) - e / f
+}
+
+/// Linear radiance that Hable's curve maps to white.
+const HABLE_WHITE: f64 = 11.2;
+
+impl ToneMap {
+    /// Maps one linear channel value into `[0, 1]`.
+    pub fn apply(self, x: f64) -> f64 {
+        let x = x.max(0.0);
+        let mapped = match self {
+            ToneMap::Clamp => x,
+            ToneMap::Reinhard => x / (1.0 + x),
+            ToneMap::Aces => (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14),
+            ToneMap::Hable => hable_partial(2.0 * x) / hable_partial(HABLE_WHITE),
+        };
+        mapped.clamp(0.0, 1.0)
+    }
+}
+
+/// The sRGB opto-electronic transfer function: encodes a linear value in
+/// `[0, 1]` the way displays expect.
+pub fn srgb_oetf(linear: f64) -> f64 {
+    if linear <= 0.003_130_8 {
+        12.92 * linear
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Everything that happens to a linear pixel on its way to an 8-bit image:
+/// exposure, tone mapping and sRGB encoding.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DisplayTransform {
+    /// Brightness adjustment in stops: each one doubles the radiance.
+    pub exposure: f64,
+    pub tone_map: ToneMap,
+}
+
+impl DisplayTransform {
+    /// The sRGB-encoded display color for linear radiance `color`, with each
+    /// channel in `[0, 1]`.
+    pub fn apply(&self, color: Color) -> Color {
+        let scale = self.exposure.exp2();
+        let encode = |c: f64| srgb_oetf(self.tone_map.apply(scale * c));
+        Color::new(encode(color.x), encode(color.y), encode(color.z))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_srgb_oetf() {
+        assert_eq!(0.0, srgb_oetf(0.0));
+        assert!(approx_eq!(f64, srgb_oetf(1.0), 1.0, epsilon = 1e-12));
+        assert!(approx_eq!(f64, srgb_oetf(0.18), 0.4614, epsilon = 1e-4));
+        // The two pieces meet at the linear segment's end, to within the
+        // rounding of the standard's constants.
+        let knee = 0.003_130_8;
+        assert!(approx_eq!(
+            f64,
+            srgb_oetf(knee),
+            srgb_oetf(knee + 1e-12),
+            epsilon = 1e-7
+        ));
+    }
+
+    #[test]
+    fn test_tone_maps() {
+        let all = [
+            ToneMap::Clamp,
+            ToneMap::Reinhard,
+            ToneMap::Aces,
+            ToneMap::Hable,
+        ];
+        for &tone_map in all.iter() {
+            assert_eq!(0.0, tone_map.apply(0.0));
+            assert_eq!(0.0, tone_map.apply(-1.0));
+            // Brighter stays brighter, and nothing leaves [0, 1].
+            let mut previous = 0.0;
+            for i in 1..200 {
+                let value = tone_map.apply(i as f64 * 0.1);
+                assert!(value >= previous && value <= 1.0, "{:?}", tone_map);
+                previous = value;
+            }
+        }
+        assert_eq!(1.0, ToneMap::Clamp.apply(4.0));
+        assert_eq!(0.8, ToneMap::Reinhard.apply(4.0));
+        assert!(approx_eq!(
+            f64,
+            ToneMap::Hable.apply(HABLE_WHITE / 2.0),
+            1.0,
+            epsilon = 1e-12
+        ));
+        assert!(ToneMap::Aces.apply(100.0) > 0.99);
+    }
+
+    #[test]
+    fn test_exposure() {
+        let grey = Color::new(0.125, 0.125, 0.125);
+        let display = DisplayTransform {
+            exposure: 2.0,
+            tone_map: ToneMap::Clamp,
+        };
+        assert_eq!(
+            DisplayTransform::default().apply(4.0 * grey),
+            display.apply(grey)
+        );
+    }
+}
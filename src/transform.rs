@@ -0,0 +1,252 @@
+use crate::aabb::Aabb;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+type Matrix = [[f64; 4]; 4];
+
+const IDENTITY: Matrix = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut product = [[0.0; 4]; 4];
+    for (i, row) in product.iter_mut().enumerate() {
+        for (j, entry) in row.iter_mut().enumerate() {
+            *entry = (0..4).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    product
+}
+
+/// An affine transform as a 4x4 matrix, kept together with its inverse so
+/// neither ever has to be computed from the other.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    matrix: Matrix,
+    inverse: Matrix,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::identity()
+    }
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Transform {
+            matrix: IDENTITY,
+            inverse: IDENTITY,
+        }
+    }
+
+    pub fn translation(offset: Vec3) -> Self {
+        let mut matrix = IDENTITY;
+        let mut inverse = IDENTITY;
+        for axis in 0..3 {
+            matrix[axis][3] = offset[axis];
+            inverse[axis][3] = -offset[axis];
+        }
+        Transform { matrix, inverse }
+    }
+
+    /// Scales each axis by the matching component of `factors`, which must all
+    /// be non-zero.
+    pub fn scaling(factors: Vec3) -> Self {
+        assert!(
+            factors.x != 0.0 && factors.y != 0.0 && factors.z != 0.0,
+            "Scaling by zero is not invertible"
+        );
+        let mut matrix = IDENTITY;
+        let mut inverse = IDENTITY;
+        for axis in 0..3 {
+            matrix[axis][axis] = factors[axis];
+            inverse[axis][axis] = 1.0 / factors[axis];
+        }
+        Transform { matrix, inverse }
+    }
+
+    /// Rotates counter-clockwise by `degrees` about `axis`, looking down the
+    /// axis towards the origin.
+    pub fn rotation(axis: Vec3, degrees: f64) -> Self {
+        let a = axis.unit_vector();
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let t = 1.0 - cos;
+        let rotation = [
+            [
+                t * a.x * a.x + cos,
+                t * a.x * a.y - sin * a.z,
+                t * a.x * a.z + sin * a.y,
+            ],
+            [
+                t * a.x * a.y + sin * a.z,
+                t * a.y * a.y + cos,
+                t * a.y * a.z - sin * a.x,
+            ],
+            [
+                t * a.x * a.z - sin * a.y,
+                t * a.y * a.z + sin * a.x,
+                t * a.z * a.z + cos,
+            ],
+        ];
+
+        // Rotations are orthogonal, so the inverse is the transpose.
+        let mut matrix = IDENTITY;
+        let mut inverse = IDENTITY;
+        for i in 0..3 {
+            for j in 0..3 {
+                matrix[i][j] = rotation[i][j];
+                inverse[j][i] = rotation[i][j];
+            }
+        }
+        Transform { matrix, inverse }
+    }
+
+    /// The transform that applies `self` first and then `next`.
+    pub fn then(self, next: Transform) -> Self {
+        Transform {
+            matrix: multiply(&next.matrix, &self.matrix),
+            inverse: multiply(&self.inverse, &next.inverse),
+        }
+    }
+
+    pub fn inverse(self) -> Self {
+        Transform {
+            matrix: self.inverse,
+            inverse: self.matrix,
+        }
+    }
+
+    pub fn matrix(&self) -> [[f64; 4]; 4] {
+        self.matrix
+    }
+
+    pub fn point(&self, p: Point3) -> Point3 {
+        let m = &self.matrix;
+        Point3::new(
+            m[0][0] * p.x + m[0][1] * p.y + m[0][2] * p.z + m[0][3],
+            m[1][0] * p.x + m[1][1] * p.y + m[1][2] * p.z + m[1][3],
+            m[2][0] * p.x + m[2][1] * p.y + m[2][2] * p.z + m[2][3],
+        )
+    }
+
+    /// Transforms a direction, which unlike a point is unaffected by
+    /// translation.
+    pub fn vector(&self, v: Vec3) -> Vec3 {
+        let m = &self.matrix;
+        Vec3::new(
+            m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z,
+            m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z,
+            m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z,
+        )
+    }
+
+    /// Transforms a surface normal by the inverse transpose, so it stays
+    /// perpendicular to the surface under non-uniform scaling. The result is
+    /// not normalized.
+    pub fn normal(&self, n: Vec3) -> Vec3 {
+        let m = &self.inverse;
+        Vec3::new(
+            m[0][0] * n.x + m[1][0] * n.y + m[2][0] * n.z,
+            m[0][1] * n.x + m[1][1] * n.y + m[2][1] * n.z,
+            m[0][2] * n.x + m[1][2] * n.y + m[2][2] * n.z,
+        )
+    }
+
+    /// Transforms a ray without normalizing its direction, so hit distances
+    /// `t` mean the same thing on both sides of the transform.
+    pub fn ray(&self, ray: &Ray) -> Ray {
+        Ray::with_time(self.point(ray.origin), self.vector(ray.direction), ray.time)
+    }
+
+    /// The axis-aligned box enclosing the transformed corners of `bbox`.
+    pub fn bounding_box(&self, bbox: Aabb) -> Aabb {
+        let corner = |i: usize| {
+            let mut p = bbox.minimum;
+            for axis in 0..3 {
+                if i & (1 << axis) != 0 {
+                    p[axis] = bbox.maximum[axis];
+                }
+            }
+            p
+        };
+        let first = self.point(corner(0));
+        (1..8)
+            .map(|i| self.point(corner(i)))
+            .fold(Aabb::new(first, first), |bbox, p| {
+                Aabb::surrounding_box(bbox, Aabb::new(p, p))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_vec_eq(exp: Vec3, act: Vec3) {
+        for i in 0..3 {
+            assert!(
+                approx_eq!(f64, exp[i], act[i], epsilon = 1e-12),
+                "expected {:?}, got {:?}",
+                exp,
+                act
+            );
+        }
+    }
+
+    #[test]
+    fn test_translation_moves_points_not_vectors() {
+        let translate = Transform::translation(Vec3::new(1.0, 2.0, 3.0));
+        let v = Vec3::new(1.0, 1.0, 1.0);
+        assert_eq!(Point3::new(2.0, 3.0, 4.0), translate.point(v));
+        assert_eq!(v, translate.vector(v));
+    }
+
+    #[test]
+    fn test_rotation() {
+        let rotate = Transform::rotation(Vec3::new(0.0, 1.0, 0.0), 90.0);
+        assert_vec_eq(
+            Vec3::new(0.0, 0.0, -1.0),
+            rotate.point(Point3::new(1.0, 0.0, 0.0)),
+        );
+        assert_vec_eq(
+            Vec3::new(1.0, 0.0, 0.0),
+            rotate.point(Point3::new(0.0, 0.0, 1.0)),
+        );
+    }
+
+    #[test]
+    fn test_then_and_inverse() {
+        let transform = Transform::scaling(Vec3::new(2.0, 1.0, 1.0))
+            .then(Transform::rotation(Vec3::new(0.0, 0.0, 1.0), 90.0))
+            .then(Transform::translation(Vec3::new(0.0, 0.0, 5.0)));
+        let p = Point3::new(1.0, 0.0, 0.0);
+        let moved = transform.point(p);
+        assert_vec_eq(Point3::new(0.0, 2.0, 5.0), moved);
+        assert_vec_eq(p, transform.inverse().point(moved));
+    }
+
+    #[test]
+    fn test_normal_stays_perpendicular() {
+        let squash = Transform::scaling(Vec3::new(1.0, 0.25, 1.0));
+        let tangent = Vec3::new(1.0, -1.0, 0.0);
+        let normal = Vec3::new(1.0, 1.0, 0.0);
+        let dot = squash.vector(tangent).dot(squash.normal(normal));
+        assert!(approx_eq!(f64, 0.0, dot, epsilon = 1e-12));
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let rotate = Transform::rotation(Vec3::new(0.0, 0.0, 1.0), 45.0);
+        let bbox = rotate.bounding_box(Aabb::new(
+            Point3::new(-1.0, -1.0, -1.0),
+            Point3::new(1.0, 1.0, 1.0),
+        ));
+        let r = 2.0f64.sqrt();
+        assert_vec_eq(Point3::new(-r, -r, -1.0), bbox.minimum);
+        assert_vec_eq(Point3::new(r, r, 1.0), bbox.maximum);
+    }
+}
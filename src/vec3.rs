@@ -46,8 +46,7 @@ impl Vec3 {
         self / self.length()
     }
 
-    pub fn random(min: f64, max: f64) -> Self {
-        let mut rng = rand::thread_rng();
+    pub fn random<R: Rng + ?Sized>(rng: &mut R, min: f64, max: f64) -> Self {
         Vec3 {
             x: rng.gen_range(min, max),
             y: rng.gen_range(min, max),
@@ -55,30 +54,21 @@ impl Vec3 {
         }
     }
 
-    pub fn random_range(min: f64, max: f64) -> Self {
-        let mut rng = rand::thread_rng();
-        Vec3 {
-            x: rng.gen_range(min, max),
-            y: rng.gen_range(min, max),
-            z: rng.gen_range(min, max),
-        }
-    }
-
-    pub fn random_in_unit_sphere() -> Self {
+    pub fn random_in_unit_sphere<R: Rng + ?Sized>(rng: &mut R) -> Self {
         loop {
-            let p = Vec3::random_range(-1.0, 1.0);
+            let p = Vec3::random(rng, -1.0, 1.0);
             if p.length_squared() < 1.0 {
                 return p;
             }
         }
     }
 
-    pub fn random_unit_vector() -> Self {
-        Vec3::unit_vector(Vec3::random_in_unit_sphere())
+    pub fn random_unit_vector<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Vec3::unit_vector(Vec3::random_in_unit_sphere(rng))
     }
 
-    pub fn random_in_hemisphere(normal: Vec3) -> Self {
-        let in_unit_sphere = Vec3::random_in_unit_sphere();
+    pub fn random_in_hemisphere<R: Rng + ?Sized>(rng: &mut R, normal: Vec3) -> Self {
+        let in_unit_sphere = Vec3::random_in_unit_sphere(rng);
         let in_same_hemisphere_as_normal = in_unit_sphere.dot(normal) > 0.0;
         if in_same_hemisphere_as_normal {
             in_unit_sphere
@@ -87,8 +77,7 @@ impl Vec3 {
         }
     }
 
-    pub fn random_in_unit_disk() -> Self {
-        let mut rng = rand::thread_rng();
+    pub fn random_in_unit_disk<R: Rng + ?Sized>(rng: &mut R) -> Self {
         loop {
             let p = Vec3::new(rng.gen_range(-1.0, 1.0), rng.gen_range(-1.0, 1.0), 0.0);
             if p.length_squared() < 1.0 {
@@ -114,6 +103,12 @@ impl Vec3 {
     }
 }
 
+impl From<[f64; 3]> for Vec3 {
+    fn from([x, y, z]: [f64; 3]) -> Self {
+        Vec3 { x, y, z }
+    }
+}
+
 impl Add<Vec3> for Vec3 {
     type Output = Vec3;
 
@@ -421,6 +416,11 @@ mod tests {
         assert_eq!(exp, act);
     }
 
+    #[test]
+    fn test_from_array() {
+        assert_eq!(Vec3::new(1.0, 2.0, 3.0), Vec3::from([1.0, 2.0, 3.0]));
+    }
+
     #[test]
     fn test_sum() {
         let sum: Vec3 = vec![Vec3::new(0.0, 1.0, 2.0), Vec3::new(2.0, 1.0, 0.0)]
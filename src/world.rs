@@ -1,17 +1,26 @@
+use crate::camera::Camera;
+use crate::hittable::moving_sphere::MovingSphere;
+use crate::hittable::plane::Plane;
 use crate::hittable::sphere::Sphere;
 use crate::hittable::Hittable;
 use crate::material::dielectric::Dielectric;
+use crate::material::diffuse_light::DiffuseLight;
 use crate::material::lambertian::Lambertian;
 use crate::material::metal::Metal;
-use crate::vec3::{Color, Point3};
+use crate::material::tagged::TaggedMaterial;
+use crate::texture::checker::CheckerTexture;
+use crate::texture::noise::{CloudTexture, MarbleTexture, WoodTexture};
+use crate::texture::perlin::Perlin;
+use crate::texture::solid_color::SolidColor;
+use crate::vec3::{Color, Point3, Vec3};
 use rand::Rng;
 
 #[allow(dead_code)]
 pub fn test_world() -> Vec<Box<dyn Hittable + Send + Sync>> {
-    let material_ground = Lambertian::new(Color::new(0.8, 0.8, 0.0));
-    let material_center = Lambertian::new(Color::new(0.1, 0.2, 0.5));
-    let material_left = Dielectric::new(1.5);
-    let material_right = Metal::new(Color::new(0.8, 0.6, 0.2), 0.0);
+    let material_ground = TaggedMaterial::new(Lambertian::new(Color::new(0.8, 0.8, 0.0)), 1);
+    let material_center = TaggedMaterial::new(Lambertian::new(Color::new(0.1, 0.2, 0.5)), 2);
+    let material_left = TaggedMaterial::new(Dielectric::new(1.5), 3);
+    let material_right = TaggedMaterial::new(Metal::new(Color::new(0.8, 0.6, 0.2), 0.0), 4);
     vec![
         Box::new(Sphere::new(
             Point3::new(0.0, -100.5, -1.0),
@@ -41,17 +50,33 @@ pub fn test_world() -> Vec<Box<dyn Hittable + Send + Sync>> {
     ]
 }
 
+/// The final scene of the book. The small spheres are placed and colored using
+/// `rng`, so a seeded generator always produces the same scene. Each sphere has
+/// its own material, numbered from the ground's 1 in the order they are made.
 #[allow(dead_code)]
-pub fn book_cover_scene() -> Vec<Box<dyn Hittable + Send + Sync>> {
-    let mut scene: Vec<Box<dyn Hittable + Send + Sync>> = Vec::new();
+pub fn book_cover_scene<R: Rng + ?Sized>(rng: &mut R) -> Vec<Box<dyn Hittable + Send + Sync>> {
+    random_spheres(rng, false)
+}
 
-    let mut rng = rand::thread_rng();
+/// The book cover scene with the small diffuse spheres bouncing upwards between
+/// times 0 and 1. Render it with `bouncing_spheres_camera` to see the blur.
+#[allow(dead_code)]
+pub fn bouncing_spheres<R: Rng + ?Sized>(rng: &mut R) -> Vec<Box<dyn Hittable + Send + Sync>> {
+    random_spheres(rng, true)
+}
+
+fn random_spheres<R: Rng + ?Sized>(
+    rng: &mut R,
+    bouncing: bool,
+) -> Vec<Box<dyn Hittable + Send + Sync>> {
+    let mut scene: Vec<Box<dyn Hittable + Send + Sync>> = Vec::new();
+    let mut material_id = 1;
 
     // Ground
-    scene.push(Box::new(Sphere::new(
-        Point3::new(0.0, -1000.0, 0.0),
-        1000.0,
-        Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+    scene.push(Box::new(Plane::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        TaggedMaterial::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)), material_id),
     )));
 
     // Scattered spheres
@@ -68,15 +93,26 @@ pub fn book_cover_scene() -> Vec<Box<dyn Hittable + Send + Sync>> {
             );
 
             if (center - Point3::new(4.0, 0.2, 0.0)).length() > 0.9 {
+                material_id += 1;
                 if choose_mat < 0.8 {
-                    let albedo = Color::random(0.0, 1.0) * Color::random(0.0, 1.0);
-                    scene.push(Box::new(Sphere::new(center, 0.2, Lambertian::new(albedo))));
+                    let albedo = Color::random(rng, 0.0, 1.0) * Color::random(rng, 0.0, 1.0);
+                    let material = TaggedMaterial::new(Lambertian::new(albedo), material_id);
+                    if bouncing {
+                        let center1 = center + Vec3::new(0.0, rng.gen_range(0.0, 0.5), 0.0);
+                        scene.push(Box::new(MovingSphere::new(
+                            center, center1, 0.0, 1.0, 0.2, material,
+                        )));
+                    } else {
+                        scene.push(Box::new(Sphere::new(center, 0.2, material)));
+                    }
                 } else if choose_mat < 0.95 {
-                    let albedo = Color::random(0.5, 1.0);
+                    let albedo = Color::random(rng, 0.5, 1.0);
                     let fuzz = rng.gen_range(0.0, 0.5);
-                    scene.push(Box::new(Sphere::new(center, 0.2, Metal::new(albedo, fuzz))));
+                    let material = TaggedMaterial::new(Metal::new(albedo, fuzz), material_id);
+                    scene.push(Box::new(Sphere::new(center, 0.2, material)));
                 } else {
-                    scene.push(Box::new(Sphere::new(center, 0.2, Dielectric::new(1.5))));
+                    let material = TaggedMaterial::new(Dielectric::new(1.5), material_id);
+                    scene.push(Box::new(Sphere::new(center, 0.2, material)));
                 }
             }
         }
@@ -86,20 +122,188 @@ pub fn book_cover_scene() -> Vec<Box<dyn Hittable + Send + Sync>> {
     scene.push(Box::new(Sphere::new(
         Point3::new(0.0, 1.0, 0.0),
         1.0,
-        Dielectric::new(1.5),
+        TaggedMaterial::new(Dielectric::new(1.5), material_id + 1),
     )));
 
     scene.push(Box::new(Sphere::new(
         Point3::new(-4.0, 1.0, 0.0),
         1.0,
-        Lambertian::new(Color::new(0.4, 0.2, 0.1)),
+        TaggedMaterial::new(Lambertian::new(Color::new(0.4, 0.2, 0.1)), material_id + 2),
     )));
 
     scene.push(Box::new(Sphere::new(
         Point3::new(4.0, 1.0, 0.0),
         1.0,
-        Metal::new(Color::new(0.7, 0.6, 0.5), 0.0),
+        TaggedMaterial::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0), material_id + 3),
     )));
 
     scene
 }
+
+/// A sphere lit only by an overhead spherical light. Render it against a black
+/// background.
+#[allow(dead_code)]
+pub fn simple_light() -> Vec<Box<dyn Hittable + Send + Sync>> {
+    vec![
+        Box::new(Sphere::new(
+            Point3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            TaggedMaterial::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)), 1),
+        )),
+        Box::new(Sphere::new(
+            Point3::new(0.0, 2.0, 0.0),
+            2.0,
+            TaggedMaterial::new(Lambertian::new(Color::new(0.2, 0.4, 0.8)), 2),
+        )),
+        Box::new(simple_light_sphere()),
+    ]
+}
+
+/// The light in `simple_light`, to be sampled directly.
+#[allow(dead_code)]
+pub fn simple_light_sources() -> Vec<Box<dyn Hittable + Send + Sync>> {
+    vec![Box::new(simple_light_sphere())]
+}
+
+/// The overhead light shared by `simple_light` and `simple_light_sources`.
+fn simple_light_sphere() -> Sphere<TaggedMaterial<DiffuseLight>> {
+    Sphere::new(
+        Point3::new(0.0, 7.0, 0.0),
+        2.0,
+        TaggedMaterial::new(DiffuseLight::new(Color::new(4.0, 4.0, 4.0)), 3),
+    )
+}
+
+/// Two large spheres sharing one checkered material.
+#[allow(dead_code)]
+pub fn checkered_spheres() -> Vec<Box<dyn Hittable + Send + Sync>> {
+    let checker = CheckerTexture::new(
+        0.32,
+        SolidColor::new(Color::new(0.2, 0.3, 0.1)),
+        SolidColor::new(Color::new(0.9, 0.9, 0.9)),
+    );
+    let material = TaggedMaterial::new(Lambertian::textured(checker), 1);
+    vec![
+        Box::new(Sphere::new(Point3::new(0.0, -10.0, 0.0), 10.0, material)),
+        Box::new(Sphere::new(Point3::new(0.0, 10.0, 0.0), 10.0, material)),
+    ]
+}
+
+/// Marble, wood and cloud spheres on a marble floor. The noise is generated
+/// from `rng`, so a seeded generator always produces the same textures.
+#[allow(dead_code)]
+pub fn perlin_spheres<R: Rng + ?Sized>(rng: &mut R) -> Vec<Box<dyn Hittable + Send + Sync>> {
+    let marble = MarbleTexture::new(
+        Perlin::new(rng),
+        4.0,
+        Color::new(0.9, 0.9, 0.88),
+        Color::new(0.2, 0.2, 0.25),
+    );
+    let wood = WoodTexture::new(
+        Perlin::new(rng),
+        1.0,
+        4.0,
+        Color::new(0.8, 0.6, 0.35),
+        Color::new(0.4, 0.22, 0.1),
+    );
+    let clouds = CloudTexture::new(
+        Perlin::new(rng),
+        1.5,
+        6,
+        Color::new(0.3, 0.5, 0.9),
+        Color::new(1.0, 1.0, 1.0),
+    );
+    let marble = TaggedMaterial::new(Lambertian::textured(marble), 1);
+    vec![
+        Box::new(Sphere::new(
+            Point3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            marble.clone(),
+        )),
+        Box::new(Sphere::new(Point3::new(0.0, 2.0, 0.0), 2.0, marble)),
+        Box::new(Sphere::new(
+            Point3::new(-1.5, 1.0, 3.0),
+            1.0,
+            TaggedMaterial::new(Lambertian::textured(wood), 2),
+        )),
+        Box::new(Sphere::new(
+            Point3::new(1.5, 1.0, -3.0),
+            1.0,
+            TaggedMaterial::new(Lambertian::textured(clouds), 3),
+        )),
+    ]
+}
+
+/// Camera used to frame `test_world`.
+pub fn test_world_camera(aspect_ratio: f64) -> Camera {
+    let look_from = Point3::new(3.0, 3.0, 2.0);
+    let look_at = Point3::new(0.0, 0.0, -1.0);
+    let focus_dist = (look_from - look_at).length();
+    Camera::new(
+        look_from,
+        look_at,
+        Vec3::new(0.0, 1.0, 0.0),
+        20.0,
+        aspect_ratio,
+        2.0,
+        focus_dist,
+    )
+}
+
+/// Camera used to frame `book_cover_scene`.
+pub fn book_cover_camera(aspect_ratio: f64) -> Camera {
+    Camera::new(
+        Point3::new(13.0, 2.0, 3.0),
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        20.0,
+        aspect_ratio,
+        0.1,
+        10.0,
+    )
+}
+
+/// Camera used to frame `bouncing_spheres`, with the shutter open from time 0
+/// to 1.
+pub fn bouncing_spheres_camera(aspect_ratio: f64) -> Camera {
+    book_cover_camera(aspect_ratio).with_shutter(0.0, 1.0)
+}
+
+/// Camera used to frame `simple_light`.
+pub fn simple_light_camera(aspect_ratio: f64) -> Camera {
+    Camera::new(
+        Point3::new(26.0, 3.0, 6.0),
+        Point3::new(0.0, 2.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        20.0,
+        aspect_ratio,
+        0.0,
+        10.0,
+    )
+}
+
+/// Camera used to frame `checkered_spheres`.
+pub fn checkered_spheres_camera(aspect_ratio: f64) -> Camera {
+    Camera::new(
+        Point3::new(13.0, 2.0, 3.0),
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        20.0,
+        aspect_ratio,
+        0.0,
+        10.0,
+    )
+}
+
+/// Camera used to frame `perlin_spheres`.
+pub fn perlin_spheres_camera(aspect_ratio: f64) -> Camera {
+    Camera::new(
+        Point3::new(13.0, 2.0, 3.0),
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        20.0,
+        aspect_ratio,
+        0.0,
+        10.0,
+    )
+}
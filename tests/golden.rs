@@ -0,0 +1,190 @@
+//! Golden-image regression tests.
+//!
+//! Each test renders a small, fixed-seed version of a built-in scene and
+//! compares it with a reference PNG in `tests/golden`. Renders are
+//! deterministic, so on one machine they match exactly; the PSNR threshold
+//! leaves room for floating-point differences between platforms.
+//!
+//! On failure the render and an amplified difference image are written under
+//! the target directory, and the panic message says where. After an intended
+//! change to the output, regenerate the references with
+//! `UPDATE_GOLDEN=1 cargo test --test golden`.
+
+use image::{ImageBuffer, Rgb, RgbImage};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use ray_tracing_in_one_weekend::camera::Camera;
+use ray_tracing_in_one_weekend::hittable::bvh::BvhNode;
+use ray_tracing_in_one_weekend::hittable::Hittable;
+use ray_tracing_in_one_weekend::integrator::IntegratorKind;
+use ray_tracing_in_one_weekend::output::to_rgb8;
+use ray_tracing_in_one_weekend::render::{render_image, Background, Bounds, RenderParams};
+use ray_tracing_in_one_weekend::tone_map::DisplayTransform;
+use ray_tracing_in_one_weekend::world;
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// Renders must be at least this close to the reference, in decibels.
+const MIN_PSNR: f64 = 35.0;
+
+/// Differences are scaled up by this much in the diff image.
+const DIFF_GAIN: u32 = 8;
+
+fn render(
+    objects: Vec<Box<dyn Hittable + Send + Sync>>,
+    camera: Camera,
+    bounds: Bounds,
+    samples_per_pixel: usize,
+) -> RgbImage {
+    let params = RenderParams {
+        samples_per_pixel,
+        depth: 10,
+        background: Background::Sky,
+        integrator: IntegratorKind::Path,
+        seed: 1,
+        adaptive: None,
+    };
+    // The objects go in a hierarchy, as they do in the renderer itself.
+    let world = BvhNode::new(objects);
+    let integrator = params.integrator.build(params.depth);
+    let frame = render_image(
+        bounds,
+        &world,
+        &[],
+        camera,
+        integrator.as_ref(),
+        params,
+        &[],
+    )
+    .unwrap();
+    to_rgb8(
+        &frame.pixels,
+        bounds,
+        samples_per_pixel,
+        DisplayTransform::default(),
+    )
+}
+
+/// Root mean square difference over all channels, with channels in `[0, 1]`.
+fn rmse(a: &RgbImage, b: &RgbImage) -> f64 {
+    let sum: f64 = a
+        .as_raw()
+        .iter()
+        .zip(b.as_raw())
+        .map(|(&x, &y)| {
+            let d = (x as f64 - y as f64) / 255.0;
+            d * d
+        })
+        .sum();
+    (sum / a.as_raw().len() as f64).sqrt()
+}
+
+/// Peak signal-to-noise ratio in decibels; infinite for identical images.
+fn psnr(rmse: f64) -> f64 {
+    20.0 * (1.0 / rmse).log10()
+}
+
+fn diff_image(a: &RgbImage, b: &RgbImage) -> RgbImage {
+    ImageBuffer::from_fn(a.width(), a.height(), |x, y| {
+        let (pa, pb) = (a.get_pixel(x, y), b.get_pixel(x, y));
+        let channel = |i: usize| {
+            let d = (pa[i] as i32 - pb[i] as i32).unsigned_abs() * DIFF_GAIN;
+            d.min(255) as u8
+        };
+        Rgb([channel(0), channel(1), channel(2)])
+    })
+}
+
+fn check_golden(name: &str, actual: RgbImage) {
+    let reference_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.png", name));
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        actual.save(&reference_path).unwrap();
+        return;
+    }
+
+    let reference = image::open(&reference_path)
+        .unwrap_or_else(|err| {
+            panic!(
+                "cannot read {}: {} (set UPDATE_GOLDEN=1 to create it)",
+                reference_path.display(),
+                err
+            )
+        })
+        .to_rgb8();
+    assert_eq!(
+        reference.dimensions(),
+        actual.dimensions(),
+        "{} changed size",
+        name
+    );
+
+    let error = rmse(&reference, &actual);
+    if psnr(error) < MIN_PSNR {
+        let out_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("golden");
+        std::fs::create_dir_all(&out_dir).unwrap();
+        let actual_path = out_dir.join(format!("{}.png", name));
+        let diff_path = out_dir.join(format!("{}-diff.png", name));
+        actual.save(&actual_path).unwrap();
+        diff_image(&reference, &actual).save(&diff_path).unwrap();
+        panic!(
+            "{} differs from its reference: RMSE {:.4}, PSNR {:.1} dB (minimum {} dB)\n\
+             render: {}\ndiff: {}",
+            name,
+            error,
+            psnr(error),
+            MIN_PSNR,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+#[test]
+fn test_world() {
+    let bounds = Bounds {
+        width: 96,
+        height: 64,
+    };
+    let image = render(
+        world::test_world(),
+        world::test_world_camera(1.5),
+        bounds,
+        16,
+    );
+    check_golden("test_world", image);
+}
+
+#[test]
+fn book_cover() {
+    let bounds = Bounds {
+        width: 128,
+        height: 72,
+    };
+    let mut rng = StdRng::seed_from_u64(1);
+    let image = render(
+        world::book_cover_scene(&mut rng),
+        world::book_cover_camera(16.0 / 9.0),
+        bounds,
+        8,
+    );
+    check_golden("book_cover", image);
+}
+
+#[test]
+fn test_metrics() {
+    let black: RgbImage = ImageBuffer::new(4, 4);
+    let mut speck = black.clone();
+    speck.put_pixel(1, 2, Rgb([255, 255, 255]));
+
+    assert_eq!(0.0, rmse(&black, &black));
+    assert_eq!(f64::INFINITY, psnr(0.0));
+    // One of 16 pixels is fully wrong: RMSE 1/4, PSNR 20 log10(4).
+    assert_eq!(0.25, rmse(&black, &speck));
+    assert!((psnr(0.25) - 12.041).abs() < 1e-3);
+    assert_eq!(
+        Rgb([255, 255, 255]),
+        *diff_image(&black, &speck).get_pixel(1, 2)
+    );
+}
@@ -0,0 +1,419 @@
+//! Statistical tests of the materials' physics.
+//!
+//! Every material is checked for energy conservation in a white furnace, and
+//! the ones that sample a density are checked for reciprocity and with a
+//! chi-square test of the directions they draw. Glass is checked against
+//! the Fresnel reflectance it should show at normal and grazing incidence.
+//!
+//! All randomness comes from fixed seeds, so the tests either always pass or
+//! always fail. A new material should be added to `materials` below, and to
+//! the other lists that fit it.
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use ray_tracing_in_one_weekend::hittable::HitRecord;
+use ray_tracing_in_one_weekend::material::dielectric::Dielectric;
+use ray_tracing_in_one_weekend::material::diffuse_light::DiffuseLight;
+use ray_tracing_in_one_weekend::material::isotropic::Isotropic;
+use ray_tracing_in_one_weekend::material::lambertian::Lambertian;
+use ray_tracing_in_one_weekend::material::metal::Metal;
+use ray_tracing_in_one_weekend::material::{Material, Sampling};
+use ray_tracing_in_one_weekend::pdf::Pdf;
+use ray_tracing_in_one_weekend::ray::Ray;
+use ray_tracing_in_one_weekend::vec3::{Color, Point3, Vec3};
+use std::f64::consts::PI;
+
+const WHITE: Color = Color {
+    x: 1.0,
+    y: 1.0,
+    z: 1.0,
+};
+
+/// Angles of incidence, in degrees from the normal, that every test covers.
+const INCIDENCE: [f64; 5] = [0.0, 30.0, 60.0, 85.0, 89.9];
+
+/// Every material with a white albedo, and whether it should scatter all of
+/// the light that reaches it.
+fn materials() -> Vec<(&'static str, Box<dyn Material>, bool)> {
+    vec![
+        ("lambertian", Box::new(Lambertian::new(WHITE)), true),
+        ("mirror", Box::new(Metal::new(WHITE, 0.0)), true),
+        // Fuzzed rays that end up below the surface are absorbed.
+        ("fuzzy metal", Box::new(Metal::new(WHITE, 0.5)), false),
+        ("glass", Box::new(Dielectric::new(1.5)), true),
+        ("isotropic", Box::new(Isotropic::new(WHITE)), true),
+        ("light", Box::new(DiffuseLight::new(WHITE)), false),
+    ]
+}
+
+/// Direction of a ray arriving at the origin from `degrees` off the +z axis.
+fn incoming(degrees: f64) -> Vec3 {
+    let theta = degrees.to_radians();
+    Vec3::new(theta.sin(), 0.0, -theta.cos())
+}
+
+/// A hit at the origin of a ray travelling along `direction`, on a surface
+/// whose normal faces back against the ray.
+fn hit(material: &dyn Material, direction: Vec3, front_face: bool) -> (Ray, HitRecord<'_>) {
+    let normal = if direction.z < 0.0 {
+        Vec3::new(0.0, 0.0, 1.0)
+    } else {
+        Vec3::new(0.0, 0.0, -1.0)
+    };
+    let ray = Ray::new(Point3::default() - direction, direction);
+    let record = HitRecord::new(
+        Point3::default(),
+        normal,
+        material,
+        1.0,
+        (0.5, 0.5),
+        front_face,
+    );
+    (ray, record)
+}
+
+/// Estimates the fraction of the light arriving along `direction` that the
+/// material scatters, in its brightest channel, from the material's own
+/// samples. Returns the mean and its standard error.
+fn albedo(material: &dyn Material, direction: Vec3, front_face: bool, seed: u64) -> (f64, f64) {
+    let (ray, record) = hit(material, direction, front_face);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let samples = 20_000;
+    let weights: Vec<f64> = (0..samples)
+        .map(|_| {
+            let scatter = match material.scatter(ray, &record, &mut rng) {
+                Some(scatter) => scatter,
+                None => return 0.0,
+            };
+            let a = scatter.attenuation;
+            let attenuation = a.x.max(a.y).max(a.z);
+            match scatter.sampling {
+                Sampling::Specular(_) => attenuation,
+                Sampling::Pdf(pdf) => {
+                    let scattered = Ray::new(record.p, pdf.generate(&mut rng));
+                    let value = pdf.value(scattered.direction);
+                    if value > 0.0 {
+                        attenuation * material.scattering_pdf(ray, &record, scattered) / value
+                    } else {
+                        0.0
+                    }
+                }
+            }
+        })
+        .collect();
+    let mean = weights.iter().sum::<f64>() / samples as f64;
+    let variance = weights.iter().map(|w| (w - mean).powi(2)).sum::<f64>() / samples as f64;
+    (mean, (variance / samples as f64).sqrt())
+}
+
+/// Midpoint-rule integral of `f` over the sphere of directions, in bins of
+/// equal solid angle.
+fn integrate(f: impl Fn(Vec3) -> f64) -> f64 {
+    let n = 400;
+    let mut total = 0.0;
+    for i in 0..n {
+        let z = -1.0 + 2.0 * (i as f64 + 0.5) / n as f64;
+        for j in 0..n {
+            total += f(direction(z, 2.0 * PI * (j as f64 + 0.5) / n as f64));
+        }
+    }
+    total * 4.0 * PI / (n * n) as f64
+}
+
+fn direction(cos_theta: f64, phi: f64) -> Vec3 {
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    Vec3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta)
+}
+
+#[test]
+fn test_white_furnace() {
+    for (name, material, lossless) in materials() {
+        for &degrees in INCIDENCE.iter() {
+            for &front_face in [true, false].iter() {
+                let (mean, error) = albedo(material.as_ref(), incoming(degrees), front_face, 1);
+                assert!(
+                    mean <= 1.0 + 4.0 * error + 1e-9,
+                    "{} at {} degrees creates energy: albedo {} +- {}",
+                    name,
+                    degrees,
+                    mean,
+                    error
+                );
+                if lossless {
+                    assert!(
+                        (mean - 1.0).abs() < 1e-3,
+                        "{} at {} degrees loses energy: albedo {}",
+                        name,
+                        degrees,
+                        mean
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_scattering_pdf_is_normalized() {
+    // Independently of how a material samples, its scattering density must
+    // not integrate to more than its albedo.
+    for (name, material, lossless) in materials() {
+        for &degrees in INCIDENCE.iter() {
+            let (ray, record) = hit(material.as_ref(), incoming(degrees), true);
+            let mut rng = StdRng::seed_from_u64(2);
+            let samples_pdf = match material.scatter(ray, &record, &mut rng) {
+                Some(scatter) => matches!(scatter.sampling, Sampling::Pdf(_)),
+                None => false,
+            };
+            if !samples_pdf {
+                continue;
+            }
+            let total = integrate(|d| material.scattering_pdf(ray, &record, Ray::new(record.p, d)));
+            assert!(
+                total < 1.0 + 1e-3,
+                "{}: density integrates to {}",
+                name,
+                total
+            );
+            if lossless {
+                assert!(
+                    (total - 1.0).abs() < 1e-3,
+                    "{}: density integrates to {}",
+                    name,
+                    total
+                );
+            }
+        }
+    }
+}
+
+/// A random direction on the +z side of the surface.
+fn above(rng: &mut StdRng) -> Vec3 {
+    let mut d = Vec3::random_unit_vector(rng);
+    d.z = d.z.abs().max(1e-3);
+    d.unit_vector()
+}
+
+#[test]
+fn test_brdf_reciprocity() {
+    // The BRDF f(a, b) = scattering_pdf(-a -> b) / cos(b) is unchanged when
+    // light runs the other way; for a medium the phase function itself is.
+    let surfaces: Vec<Box<dyn Material>> = vec![Box::new(Lambertian::new(WHITE))];
+    let media: Vec<Box<dyn Material>> = vec![Box::new(Isotropic::new(WHITE))];
+
+    let brdf = |material: &dyn Material, a: Vec3, b: Vec3, surface: bool| {
+        let (ray, record) = hit(material, -a, true);
+        let density = material.scattering_pdf(ray, &record, Ray::new(record.p, b));
+        if surface {
+            density / b.z
+        } else {
+            density
+        }
+    };
+
+    let mut rng = StdRng::seed_from_u64(3);
+    for _ in 0..1000 {
+        let (a, b) = (above(&mut rng), above(&mut rng));
+        for (materials, surface) in [(&surfaces, true), (&media, false)].iter() {
+            for material in materials.iter() {
+                let forward = brdf(material.as_ref(), a, b, *surface);
+                let backward = brdf(material.as_ref(), b, a, *surface);
+                assert!(
+                    (forward - backward).abs() <= 1e-9 * forward.abs().max(1.0),
+                    "f({:?}, {:?}) = {} but f({:?}, {:?}) = {}",
+                    a,
+                    b,
+                    forward,
+                    b,
+                    a,
+                    backward
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_specular_paths_are_reversible() {
+    // Any direction a mirror or glass sends light along must send light
+    // arriving back along it to where it came from.
+    let specular: Vec<(Box<dyn Material>, bool)> = vec![
+        (Box::new(Metal::new(WHITE, 0.0)), true),
+        (Box::new(Dielectric::new(1.5)), true),
+        (Box::new(Dielectric::new(1.5)), false),
+    ];
+    let mut rng = StdRng::seed_from_u64(4);
+    let mut outcomes = |material: &dyn Material, direction: Vec3, front_face: bool| {
+        let (ray, record) = hit(material, direction, front_face);
+        let mut outcomes: Vec<Vec3> = Vec::new();
+        for _ in 0..200 {
+            let scatter = material.scatter(ray, &record, &mut rng).unwrap();
+            match scatter.sampling {
+                Sampling::Specular(scattered) => outcomes.push(scattered.direction.unit_vector()),
+                Sampling::Pdf(_) => panic!("expected a specular bounce"),
+            }
+        }
+        outcomes
+    };
+
+    for (material, front_face) in specular.iter() {
+        for &degrees in INCIDENCE.iter() {
+            let direction = incoming(degrees);
+            for out in outcomes(material.as_ref(), direction, *front_face) {
+                // Crossing the surface also changes which side we are on.
+                let back_face = if out.z < 0.0 {
+                    !front_face
+                } else {
+                    *front_face
+                };
+                let reversed = outcomes(material.as_ref(), -out, back_face);
+                assert!(
+                    reversed.iter().any(|r| (*r + direction).length() < 1e-9),
+                    "{:?} at {} degrees is not reversible",
+                    out,
+                    degrees
+                );
+            }
+        }
+    }
+}
+
+/// Fraction of rays reflected by glass of index `ir`, arriving at `degrees`
+/// from the normal.
+fn reflected_fraction(ir: f64, degrees: f64, front_face: bool, samples: usize) -> f64 {
+    let material = Dielectric::new(ir);
+    let (ray, record) = hit(&material, incoming(degrees), front_face);
+    let mut rng = StdRng::seed_from_u64(5);
+    let reflected = (0..samples)
+        .filter(
+            |_| match material.scatter(ray, &record, &mut rng).unwrap().sampling {
+                Sampling::Specular(scattered) => scattered.direction.dot(record.normal) > 0.0,
+                Sampling::Pdf(_) => panic!("expected a specular bounce"),
+            },
+        )
+        .count();
+    reflected as f64 / samples as f64
+}
+
+#[test]
+fn test_fresnel_reflectance() {
+    let samples = 200_000;
+    let tolerance = |r: f64| 5.0 * (r * (1.0 - r) / samples as f64).sqrt();
+
+    // At normal incidence glass reflects ((n - 1) / (n + 1))^2, from either
+    // side.
+    for &(ir, expected) in [(1.5, 0.04), (2.0, 1.0 / 9.0), (1.0, 0.0)].iter() {
+        for &front_face in [true, false].iter() {
+            let actual = reflected_fraction(ir, 0.0, front_face, samples);
+            assert!(
+                (actual - expected).abs() <= tolerance(expected) + 1e-12,
+                "index {}: expected {}, got {}",
+                ir,
+                expected,
+                actual
+            );
+        }
+    }
+
+    // Towards grazing incidence almost everything is reflected.
+    let grazing = reflected_fraction(1.5, 89.9, true, samples);
+    assert!(grazing > 0.99, "grazing reflectance {}", grazing);
+    let mut previous = 0.0;
+    for &degrees in INCIDENCE.iter() {
+        let fraction = reflected_fraction(1.5, degrees, true, samples);
+        assert!(fraction + tolerance(fraction) >= previous);
+        previous = fraction;
+    }
+
+    // Beyond the critical angle, light inside the glass cannot get out.
+    let critical = (1.0f64 / 1.5).asin().to_degrees();
+    assert_eq!(1.0, reflected_fraction(1.5, critical + 1.0, false, 1000));
+    assert!(reflected_fraction(1.5, critical - 1.0, false, 1000) < 1.0);
+}
+
+/// Chi-square statistic above which the hypothesis that samples come from
+/// the expected distribution is rejected at a 1 in 10,000 significance level,
+/// by the Wilson-Hilferty approximation.
+fn chi_square_threshold(degrees_of_freedom: usize) -> f64 {
+    let z = 3.719;
+    let k = degrees_of_freedom as f64;
+    let h = 2.0 / (9.0 * k);
+    k * (1.0 - h + z * h.sqrt()).powi(3)
+}
+
+/// Chi-square test of the directions drawn from `pdf` against its density,
+/// over bins in cos(theta) and phi about the z axis.
+fn chi_square(pdf: &dyn Pdf, seed: u64) -> (f64, f64) {
+    let (cos_bins, phi_bins, samples) = (16, 16, 100_000);
+    let bin = |d: Vec3| {
+        let cos = ((d.z + 1.0) / 2.0 * cos_bins as f64) as usize;
+        let phi = (d.y.atan2(d.x) + PI) / (2.0 * PI) * phi_bins as f64;
+        cos.min(cos_bins - 1) * phi_bins + (phi as usize).min(phi_bins - 1)
+    };
+
+    let mut observed = vec![0.0; cos_bins * phi_bins];
+    let mut rng = StdRng::seed_from_u64(seed);
+    for _ in 0..samples {
+        observed[bin(pdf.generate(&mut rng).unit_vector())] += 1.0;
+    }
+
+    let sub = 8;
+    let mut expected = vec![0.0; cos_bins * phi_bins];
+    let solid_angle = 4.0 * PI / (cos_bins * phi_bins * sub * sub) as f64;
+    for i in 0..cos_bins * sub {
+        let z = -1.0 + 2.0 * (i as f64 + 0.5) / (cos_bins * sub) as f64;
+        for j in 0..phi_bins * sub {
+            let phi = -PI + 2.0 * PI * (j as f64 + 0.5) / (phi_bins * sub) as f64;
+            let d = direction(z, phi);
+            expected[bin(d)] += pdf.value(d) * solid_angle * samples as f64;
+        }
+    }
+
+    // Bins expected to hold too few samples are pooled, as the test needs.
+    let (mut statistic, mut pooled_observed, mut pooled_expected) = (0.0, 0.0, 0.0);
+    let mut bins = 0;
+    for (o, e) in observed.iter().zip(expected.iter()) {
+        if *e < 5.0 {
+            pooled_observed += o;
+            pooled_expected += e;
+        } else {
+            statistic += (o - e).powi(2) / e;
+            bins += 1;
+        }
+    }
+    if pooled_expected >= 5.0 {
+        statistic += (pooled_observed - pooled_expected).powi(2) / pooled_expected;
+        bins += 1;
+    } else {
+        // Too little expected mass to test, so anything found there is an
+        // error.
+        statistic += pooled_observed;
+    }
+    (statistic, chi_square_threshold(bins - 1))
+}
+
+#[test]
+fn test_sampled_directions_chi_square() {
+    for (name, material, _) in materials() {
+        for &degrees in INCIDENCE.iter() {
+            let (ray, record) = hit(material.as_ref(), incoming(degrees), true);
+            let mut rng = StdRng::seed_from_u64(6);
+            let pdf = match material.scatter(ray, &record, &mut rng) {
+                Some(scatter) => match scatter.sampling {
+                    Sampling::Pdf(pdf) => pdf,
+                    Sampling::Specular(_) => continue,
+                },
+                None => continue,
+            };
+            let (statistic, threshold) = chi_square(pdf.as_ref(), 7);
+            assert!(
+                statistic < threshold,
+                "{} at {} degrees: chi-square {} exceeds {}",
+                name,
+                degrees,
+                statistic,
+                threshold
+            );
+        }
+    }
+}